@@ -0,0 +1,326 @@
+use bevy::{app::AppExit, prelude::*, utils::HashMap};
+
+use crate::enemy;
+use crate::events;
+use crate::input;
+use crate::run::RunApp;
+use crate::score;
+use crate::sets::GameSet;
+use crate::state;
+use crate::storage;
+use crate::ui::{
+    theme::{Surface, TextRole, Theme, UiText},
+    widgets,
+};
+
+pub struct AchievementPlugin;
+
+impl Plugin for AchievementPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_run_resource::<RunProgress>()
+            .add_event::<AchievementUnlocked>()
+            .add_startup_system(load_achievements)
+            .add_systems((
+                save_achievements.in_schedule(OnEnter(state::AppState::GameOver)),
+                save_achievements.in_schedule(OnEnter(state::AppState::MainMenu)),
+                // closing the window mid-run shouldn't lose what was caught so far
+                save_achievements
+                    .run_if(on_event::<AppExit>())
+                    .in_base_set(CoreSet::Last),
+            ))
+            // watching a replay doesn't earn anything
+            .add_system(
+                track_achievements
+                    .run_if(resource_equals(input::InputSource::Keyboard))
+                    .in_set(GameSet::Scoring),
+            )
+            .add_systems((show_toasts, expire_toasts).in_set(GameSet::Ui));
+    }
+}
+
+const FILE_NAME: &str = "achievements.txt";
+const HEADER: &str = "gonefishin-achievements";
+const VERSION: u32 = 1;
+
+const TOAST_SECONDS: f32 = 3.;
+
+/// What has to happen for an achievement to unlock
+#[derive(Clone, Copy, Debug)]
+pub enum Goal {
+    /// Fish brought in, over every run ever played
+    FishCaught(u32),
+    /// Fish killed by a single bomb
+    KillsWithOneBomb(u32),
+    /// Waves cleared in one run. Hitting a turtle ends the run, so these are all turtle free.
+    WavesCleared(u32),
+    /// Points in one run
+    Score(u32),
+    /// Fish brought in one after another without a long gap
+    Combo(u32),
+}
+
+pub struct AchievementDef {
+    /// Saved to disk, so it must never change once released
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub goal: Goal,
+}
+
+pub const ACHIEVEMENTS: &[AchievementDef] = &[
+    AchievementDef {
+        id: "first_catch",
+        name: "First Catch",
+        description: "Bring in your first fish",
+        goal: Goal::FishCaught(1),
+    },
+    AchievementDef {
+        id: "fifty_fish",
+        name: "Fishmonger",
+        description: "Catch 50 fish",
+        goal: Goal::FishCaught(50),
+    },
+    AchievementDef {
+        id: "triple_kill",
+        name: "Triple Kill",
+        description: "Kill three fish with one bomb",
+        goal: Goal::KillsWithOneBomb(3),
+    },
+    AchievementDef {
+        id: "careful_fisher",
+        name: "Careful Fisher",
+        description: "Finish a wave without harming a turtle",
+        goal: Goal::WavesCleared(1),
+    },
+    AchievementDef {
+        id: "five_waves",
+        name: "Stamina",
+        description: "Clear five waves in one run",
+        goal: Goal::WavesCleared(5),
+    },
+    AchievementDef {
+        id: "on_a_roll",
+        name: "On a Roll",
+        description: "Bring in three fish in a row",
+        goal: Goal::Combo(3),
+    },
+    AchievementDef {
+        id: "big_haul",
+        name: "Big Haul",
+        description: "Score 2000 points in one run",
+        goal: Goal::Score(2000),
+    },
+];
+
+/// Sent the moment an achievement unlocks, with its index in `ACHIEVEMENTS`
+pub struct AchievementUnlocked {
+    pub index: usize,
+}
+
+/// Which achievements have been unlocked, and the progress that carries between runs
+#[derive(Resource, Default)]
+pub struct Achievements {
+    /// Unix timestamp each achievement was unlocked at, by id
+    pub unlocked: HashMap<String, u64>,
+    pub fish_caught: u32,
+    file: storage::VersionedFile,
+}
+
+impl Achievements {
+    pub fn is_unlocked(&self, def: &AchievementDef) -> bool {
+        self.unlocked.contains_key(def.id)
+    }
+
+    /// How far along a locked achievement is, for goals that build up over many runs
+    pub fn progress(&self, def: &AchievementDef) -> Option<(u32, u32)> {
+        match def.goal {
+            Goal::FishCaught(n) => Some((self.fish_caught.min(n), n)),
+            _ => None,
+        }
+    }
+
+    /// Reads progress from `contents`, skipping any lines that don't make sense.
+    /// Fails only when the file was written by a newer version of the game.
+    pub fn parse(contents: &str) -> Result<Achievements, storage::Newer> {
+        let mut achievements = Achievements::default();
+
+        for line in storage::versioned_lines(contents, HEADER, VERSION)? {
+            let fields: Vec<&str> = line.split('\t').collect();
+
+            let read = match fields.as_slice() {
+                ["caught", n] => n.parse().map(|n| achievements.fish_caught = n).is_ok(),
+                ["unlocked", id, date] => date
+                    .parse()
+                    .map(|date| {
+                        achievements.unlocked.insert(id.to_string(), date);
+                    })
+                    .is_ok(),
+                _ => false,
+            };
+
+            if !read {
+                warn!("skipping unreadable achievement line {:?}", line);
+            }
+        }
+
+        Ok(achievements)
+    }
+
+    pub fn serialize(&self) -> String {
+        let mut contents = format!("{} {}\n", HEADER, VERSION);
+        contents.push_str(&format!("caught\t{}\n", self.fish_caught));
+
+        // in definition order so the file doesn't shuffle around between saves
+        for def in ACHIEVEMENTS {
+            if let Some(date) = self.unlocked.get(def.id) {
+                contents.push_str(&format!("unlocked\t{}\t{}\n", def.id, date));
+            }
+        }
+
+        contents
+    }
+
+    pub fn save(&self) {
+        self.file.save(self.serialize());
+    }
+}
+
+fn load_achievements(mut commands: Commands) {
+    let (file, achievements) = storage::VersionedFile::load(FILE_NAME, Achievements::parse);
+
+    commands.insert_resource(Achievements {
+        file,
+        ..achievements.unwrap_or_default()
+    });
+}
+
+/// Progress towards the achievements that have to happen within a single run
+#[derive(Resource, Default)]
+pub struct RunProgress {
+    pub bomb_kills: u32,
+    pub waves_cleared: u32,
+}
+
+fn save_achievements(achievements: Res<Achievements>) {
+    achievements.save();
+}
+
+#[allow(clippy::too_many_arguments)]
+fn track_achievements(
+    mut achievements: ResMut<Achievements>,
+    mut progress: ResMut<RunProgress>,
+    score: Res<score::Score>,
+    mut killed: EventReader<events::FishKilled>,
+    mut collected: EventReader<events::FishCollected>,
+    mut cleared: EventReader<events::WaveCleared>,
+    mut changed: EventReader<events::ScoreChanged>,
+    mut unlocked: EventWriter<AchievementUnlocked>,
+) {
+    for kill in killed.iter() {
+        // each bomb only has one explosion, so its first kill starts the count over
+        progress.bomb_kills = if kill.first_for_bomb {
+            1
+        } else {
+            progress.bomb_kills + 1
+        };
+    }
+
+    // turtles don't count as fish
+    achievements.fish_caught += collected
+        .iter()
+        .filter(|c| c.fish_type == enemy::FishType::Basic)
+        .count() as u32;
+    progress.waves_cleared += cleared.iter().count() as u32;
+    let best_combo = changed.iter().map(|c| c.combo).max().unwrap_or(0);
+
+    let mut any_unlocked = false;
+
+    for (index, def) in ACHIEVEMENTS.iter().enumerate() {
+        if achievements.is_unlocked(def) {
+            continue;
+        }
+
+        let done = match def.goal {
+            Goal::FishCaught(n) => achievements.fish_caught >= n,
+            Goal::KillsWithOneBomb(n) => progress.bomb_kills >= n,
+            Goal::WavesCleared(n) => progress.waves_cleared >= n,
+            Goal::Score(n) => score.val >= n,
+            Goal::Combo(n) => best_combo >= n,
+        };
+
+        if done {
+            info!("unlocked achievement {}", def.id);
+            achievements
+                .unlocked
+                .insert(def.id.to_string(), storage::now());
+            unlocked.send(AchievementUnlocked { index });
+            any_unlocked = true;
+        }
+    }
+
+    if any_unlocked {
+        achievements.save();
+    }
+}
+
+#[derive(Component)]
+struct Toast {
+    timer: Timer,
+}
+
+// stacked down the top of the screen, newest last
+fn show_toasts(
+    mut commands: Commands,
+    theme: Res<Theme>,
+    mut unlocked: EventReader<AchievementUnlocked>,
+    toast_q: Query<&Toast>,
+) {
+    for (shown, unlock) in (toast_q.iter().count()..).zip(unlocked.iter()) {
+        let def = &ACHIEVEMENTS[unlock.index];
+
+        commands
+            .spawn((
+                Toast {
+                    timer: Timer::from_seconds(TOAST_SECONDS, TimerMode::Once),
+                },
+                Surface::Panel,
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        position: UiRect {
+                            top: Val::Px(20. + 90. * shown as f32),
+                            left: Val::Px(20.),
+                            ..default()
+                        },
+                        flex_direction: FlexDirection::Column,
+                        padding: UiRect::all(Val::Px(10.)),
+                        ..default()
+                    },
+                    background_color: theme.panel.into(),
+                    z_index: ZIndex::Global(10),
+                    ..default()
+                },
+            ))
+            .with_children(|parent| {
+                parent.spawn(widgets::text_bundle(UiText::highlighted(
+                    TextRole::Small,
+                    "Achievement unlocked",
+                )));
+                widgets::text(parent, TextRole::Heading, def.name);
+            });
+    }
+}
+
+// toasts keep counting down through pauses and menus
+fn expire_toasts(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut toast_q: Query<(Entity, &mut Toast)>,
+) {
+    for (entity, mut toast) in toast_q.iter_mut() {
+        toast.timer.tick(time.raw_delta());
+        if toast.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,252 @@
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+
+use rand::Rng;
+
+use crate::level;
+use crate::run::{RunScoped, RunSet};
+use crate::sets::GameSet;
+use crate::state;
+
+pub struct CurrentPlugin;
+
+impl Plugin for CurrentPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(CurrentVisualization { enabled: false })
+            .add_system(
+                spawn_current_markers
+                    .in_set(RunSet::Setup)
+                    .in_schedule(OnEnter(state::AppState::Loading)),
+            )
+            .add_system(toggle_current_visualization)
+            .add_system(show_current_markers.in_set(GameSet::Ui))
+            .add_system(
+                apply_currents
+                    .in_set(GameSet::Movement)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
+            .add_system(
+                move_current_markers
+                    .in_set(GameSet::Ui)
+                    .in_set(OnUpdate(state::AppState::Running)),
+            );
+    }
+}
+
+// how quickly drifting things lose the speed a current gave them
+const DRAG: f32 = 1.5;
+
+#[derive(Clone, Copy, Debug)]
+pub enum CurrentKind {
+    /// Constant push in one direction, in pixels per second squared
+    Flow(Vec2),
+    /// Constant push up towards the surface
+    Upwelling(f32),
+    /// Spins things around the center of the zone while pulling them inward
+    Whirlpool { spin: f32, pull: f32 },
+}
+
+/// A rectangular region of the lake that pushes around anything drifting through it
+#[derive(Clone, Copy, Debug)]
+pub struct CurrentZone {
+    pub center: Vec2,
+    pub half_size: Vec2,
+    pub kind: CurrentKind,
+}
+
+impl CurrentZone {
+    pub fn contains(&self, pos: Vec2) -> bool {
+        let offset = (pos - self.center).abs();
+        offset.x <= self.half_size.x && offset.y <= self.half_size.y
+    }
+
+    /// The acceleration the zone applies to something at `pos`
+    pub fn force_at(&self, pos: Vec2) -> Vec2 {
+        match self.kind {
+            CurrentKind::Flow(force) => force,
+            CurrentKind::Upwelling(strength) => Vec2::new(0., strength),
+            CurrentKind::Whirlpool { spin, pull } => {
+                let offset = pos - self.center;
+                if offset.length_squared() < 1. {
+                    return Vec2::ZERO;
+                }
+
+                let inward = -offset.normalize();
+                inward.perp() * spin + inward * pull
+            }
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self.kind {
+            CurrentKind::Flow(_) => Color::rgba(0.85, 0.95, 1., 0.6),
+            CurrentKind::Upwelling(_) => Color::rgba(0.6, 1., 0.8, 0.6),
+            CurrentKind::Whirlpool { .. } => Color::rgba(0.8, 0.7, 1., 0.6),
+        }
+    }
+}
+
+/// Lets currents move an entity around.
+/// `response` scales how strongly the currents push on it.
+#[derive(Component)]
+pub struct Drift {
+    pub velocity: Vec2,
+    pub response: f32,
+}
+
+impl Drift {
+    pub fn new(response: f32) -> Self {
+        Drift {
+            velocity: Vec2::ZERO,
+            response,
+        }
+    }
+}
+
+/// Carries drifting things along, after they've moved themselves for the tick
+pub fn apply_currents(
+    fixed_time: Res<FixedTime>,
+    selected: Res<level::SelectedLevel>,
+    mut drift_q: Query<(&mut Transform, &mut Drift), Without<level::Ground>>,
+    ground_q: Query<&Transform, With<level::Ground>>,
+) {
+    let zones = selected.level().currents;
+    let surface = ground_q.single().translation.y;
+    let dt = fixed_time.period.as_secs_f32();
+
+    for (mut transform, mut drift) in drift_q.iter_mut() {
+        let pos = transform.translation.truncate();
+
+        let force: Vec2 = zones
+            .iter()
+            .filter(|zone| zone.contains(pos))
+            .map(|zone| zone.force_at(pos))
+            .sum();
+
+        let response = drift.response;
+        drift.velocity += force * response * dt;
+        drift.velocity *= (1. - DRAG * dt).max(0.);
+
+        transform.translation += (drift.velocity * dt).extend(0.);
+
+        // currents can carry things up to the surface, but not out of the water
+        if pos.y <= surface && transform.translation.y > surface {
+            transform.translation.y = surface;
+            drift.velocity.y = 0.;
+        }
+    }
+}
+
+/// Whether the streaks showing where the currents flow are drawn
+#[derive(Resource)]
+pub struct CurrentVisualization {
+    pub enabled: bool,
+}
+
+fn toggle_current_visualization(
+    keys: Res<Input<KeyCode>>,
+    mut visualization: ResMut<CurrentVisualization>,
+) {
+    if keys.just_pressed(KeyCode::V) {
+        visualization.enabled = !visualization.enabled;
+    }
+}
+
+// a streak drifting along with the water to show which way a zone flows
+#[derive(Component)]
+struct CurrentMarker {
+    zone: usize,
+}
+
+const MARKERS_PER_ZONE: usize = 12;
+
+fn random_point_in(zone: &CurrentZone) -> Vec2 {
+    let mut rng = rand::thread_rng();
+
+    zone.center
+        + Vec2::new(
+            rng.gen_range(-zone.half_size.x..zone.half_size.x),
+            rng.gen_range(-zone.half_size.y..zone.half_size.y),
+        )
+}
+
+fn spawn_current_markers(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    selected: Res<level::SelectedLevel>,
+    visualization: Res<CurrentVisualization>,
+) {
+    let visibility = if visualization.enabled {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+
+    let streak = meshes.add(shape::Quad::new(Vec2::new(8., 2.)).into());
+
+    for (i, zone) in selected.level().currents.iter().enumerate() {
+        let material = materials.add(ColorMaterial::from(zone.color()));
+
+        for _ in 0..MARKERS_PER_ZONE {
+            commands.spawn((
+                CurrentMarker { zone: i },
+                RunScoped {},
+                MaterialMesh2dBundle {
+                    mesh: streak.clone().into(),
+                    material: material.clone(),
+                    transform: Transform::from_translation(random_point_in(zone).extend(0.5)),
+                    visibility,
+                    ..default()
+                },
+            ));
+        }
+    }
+}
+
+fn show_current_markers(
+    visualization: Res<CurrentVisualization>,
+    mut marker_q: Query<&mut Visibility, With<CurrentMarker>>,
+) {
+    if !visualization.is_changed() {
+        return;
+    }
+
+    for mut visibility in marker_q.iter_mut() {
+        *visibility = if visualization.enabled {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+fn move_current_markers(
+    time: Res<Time>,
+    visualization: Res<CurrentVisualization>,
+    selected: Res<level::SelectedLevel>,
+    mut marker_q: Query<(&CurrentMarker, &mut Transform)>,
+) {
+    if !visualization.enabled {
+        return;
+    }
+
+    let zones = selected.level().currents;
+
+    for (marker, mut transform) in marker_q.iter_mut() {
+        let zone = &zones[marker.zone];
+        let pos = transform.translation.truncate();
+
+        // markers move along the force so the flow direction is easy to read
+        let flow = zone.force_at(pos);
+        transform.translation += (flow * time.delta_seconds()).extend(0.);
+        transform.rotation = Quat::from_rotation_z(flow.y.atan2(flow.x));
+
+        let pos = transform.translation.truncate();
+        let swallowed =
+            matches!(zone.kind, CurrentKind::Whirlpool { .. }) && pos.distance(zone.center) < 6.;
+
+        if swallowed || !zone.contains(pos) {
+            transform.translation = random_point_in(zone).extend(0.5);
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::ui::theme::Theme;
+
+mod console;
+mod graphs;
+mod inspector;
+mod tuning;
+
+/// Overlays for working on the game, only built with the `dev` feature.
+/// Each one is toggled on its own with the function keys.
+pub struct DevPlugin;
+
+impl Plugin for DevPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DevOverlay>()
+            .add_plugin(RapierDebugRenderPlugin::default().disabled())
+            .add_plugin(console::ConsolePlugin)
+            .add_plugin(graphs::GraphsPlugin)
+            .add_plugin(inspector::InspectorPlugin)
+            .add_plugin(tuning::TuningPlugin)
+            .add_system(toggle_overlays)
+            .add_system(show_colliders.after(toggle_overlays))
+            .add_system(free_camera.after(toggle_overlays));
+    }
+}
+
+const CAMERA_SPEED: f32 = 400.;
+// how much the view zooms in or out each second the key is held
+const CAMERA_ZOOM_SPEED: f32 = 1.;
+
+/// Which overlays are showing
+#[derive(Resource, Default)]
+pub struct DevOverlay {
+    /// F1, outlines of every collider
+    pub colliders: bool,
+    /// F2, moves the view with IJKL and zooms with U and O
+    pub free_camera: bool,
+    /// F3, how many entities there are and where every fish is
+    pub inspector: bool,
+    /// F4, frame rate and frame time
+    pub graphs: bool,
+    /// F5, spawn and speed values that can be changed while playing
+    pub tuning: bool,
+}
+
+fn toggle_overlays(keys: Res<Input<KeyCode>>, mut overlay: ResMut<DevOverlay>) {
+    for key in keys.get_just_pressed() {
+        let enabled = match key {
+            KeyCode::F1 => &mut overlay.colliders,
+            KeyCode::F2 => &mut overlay.free_camera,
+            KeyCode::F3 => &mut overlay.inspector,
+            KeyCode::F4 => &mut overlay.graphs,
+            KeyCode::F5 => &mut overlay.tuning,
+            _ => continue,
+        };
+        *enabled = !*enabled;
+    }
+}
+
+fn show_colliders(overlay: Res<DevOverlay>, mut render: ResMut<DebugRenderContext>) {
+    if overlay.is_changed() {
+        render.enabled = overlay.colliders;
+    }
+}
+
+fn free_camera(
+    time: Res<Time>,
+    keys: Res<Input<KeyCode>>,
+    overlay: Res<DevOverlay>,
+    mut camera_q: Query<(&mut Transform, &mut OrthographicProjection), With<Camera2d>>,
+) {
+    let Ok((mut transform, mut projection)) = camera_q.get_single_mut() else {
+        return;
+    };
+
+    if !overlay.free_camera {
+        // put the view back where the game expects it
+        if overlay.is_changed() {
+            transform.translation = Vec3::new(0., 0., transform.translation.z);
+            projection.scale = 1.;
+        }
+        return;
+    }
+
+    // real time, so the camera still moves while the game is paused
+    let dt = time.raw_delta_seconds();
+
+    let pan = [
+        (KeyCode::J, Vec2::NEG_X),
+        (KeyCode::L, Vec2::X),
+        (KeyCode::K, Vec2::NEG_Y),
+        (KeyCode::I, Vec2::Y),
+    ]
+    .into_iter()
+    .filter(|(key, _)| keys.pressed(*key))
+    .map(|(_, dir)| dir)
+    .sum::<Vec2>();
+
+    transform.translation += (pan * CAMERA_SPEED * projection.scale * dt).extend(0.);
+
+    if keys.pressed(KeyCode::U) {
+        projection.scale *= 1. - CAMERA_ZOOM_SPEED * dt;
+    }
+    if keys.pressed(KeyCode::O) {
+        projection.scale *= 1. + CAMERA_ZOOM_SPEED * dt;
+    }
+    projection.scale = projection.scale.clamp(0.1, 10.);
+}
+
+// a block of text in a corner of the screen, for the overlays that are mostly words
+fn text_panel(commands: &mut Commands, theme: &Theme, position: UiRect, marker: impl Bundle) {
+    commands.spawn((
+        marker,
+        TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: theme.font.clone(),
+                    font_size: theme.small_size,
+                    color: Color::WHITE,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                position,
+                padding: UiRect::all(Val::Px(6.)),
+                ..default()
+            },
+            background_color: Color::rgba(0., 0., 0., 0.6).into(),
+            visibility: Visibility::Hidden,
+            z_index: ZIndex::Global(100),
+            ..default()
+        },
+    ));
+}
+
+fn visibility(shown: bool) -> Visibility {
+    if shown {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    }
+}
@@ -0,0 +1,386 @@
+use bevy::{input::InputSystem, prelude::*};
+use rand::Rng;
+
+use std::collections::VecDeque;
+
+use crate::enemy::{self, FishPool, FishSpawnConfig, FishType};
+use crate::events;
+use crate::playfield::Playfield;
+use crate::rng;
+use crate::score;
+use crate::sets::GameSet;
+use crate::state::AppState;
+use crate::stats;
+use crate::types;
+use crate::ui::theme::Theme;
+use crate::visuals::Visuals;
+
+/// A command line for poking at a run, opened and closed with the backtick key.
+/// While it's open it takes all the keyboard input, so typing doesn't also play the game.
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Console>()
+            .init_resource::<StressTest>()
+            .add_event::<RunCommand>()
+            .add_startup_system(spawn_console)
+            .add_system(
+                type_commands
+                    .in_base_set(CoreSet::PreUpdate)
+                    .after(InputSystem)
+                    .before(GameSet::Input),
+            )
+            .add_system(run_commands)
+            .add_system(show_console.after(run_commands))
+            .add_system(keep_lake_stocked.in_set(OnUpdate(AppState::Running)));
+    }
+}
+
+// lines of earlier commands and their results kept on screen
+const LOG_LINES: usize = 12;
+
+const HELP: &str = "commands: spawn <basic|turtle> [count], set score <n>, gameover, wave <n>, \
+                    seed <n>, timescale <x>, stress <fish>, help";
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Spawn {
+        fish_type: FishType,
+        count: u32,
+    },
+    SetScore(u32),
+    GameOver,
+    Wave(u32),
+    Seed(u64),
+    TimeScale(f32),
+    /// Keeps this many fish in the lake, 0 stops
+    Stress(u32),
+    Help,
+}
+
+fn parse_number<T: std::str::FromStr>(word: Option<&str>, what: &str) -> Result<T, String> {
+    let word = word.ok_or_else(|| format!("missing {}", what))?;
+    word.parse()
+        .map_err(|_| format!("{:?} isn't a valid {}", word, what))
+}
+
+impl Command {
+    pub fn parse(line: &str) -> Result<Command, String> {
+        let mut words = line.split_whitespace();
+        let Some(name) = words.next() else {
+            return Err("type help to see the commands".to_string());
+        };
+
+        let command = match name {
+            "spawn" => {
+                let fish_type = match words.next() {
+                    Some("basic") | Some("fish") => FishType::Basic,
+                    Some("turtle") => FishType::Turtle,
+                    Some(other) => return Err(format!("there are no {:?} fish", other)),
+                    None => return Err("spawn what? basic or turtle".to_string()),
+                };
+                let count = match words.next() {
+                    Some(count) => parse_number(Some(count), "count")?,
+                    None => 1,
+                };
+                Command::Spawn { fish_type, count }
+            }
+            "set" => match words.next() {
+                Some("score") => Command::SetScore(parse_number(words.next(), "score")?),
+                _ => return Err("set what? only score can be set".to_string()),
+            },
+            "gameover" => Command::GameOver,
+            "wave" => {
+                let wave = parse_number(words.next(), "wave")?;
+                if wave == 0 {
+                    return Err("waves start from 1".to_string());
+                }
+                Command::Wave(wave)
+            }
+            "seed" => Command::Seed(parse_number(words.next(), "seed")?),
+            "timescale" => {
+                let scale: f32 = parse_number(words.next(), "time scale")?;
+                if !scale.is_finite() || scale < 0. {
+                    return Err("time scale can't be negative".to_string());
+                }
+                Command::TimeScale(scale)
+            }
+            "stress" => Command::Stress(parse_number(words.next(), "fish count")?),
+            "help" => Command::Help,
+            other => return Err(format!("unknown command {:?}, try help", other)),
+        };
+
+        match words.next() {
+            Some(extra) => Err(format!("didn't expect {:?} on the end", extra)),
+            None => Ok(command),
+        }
+    }
+}
+
+pub struct RunCommand(pub Command);
+
+#[derive(Resource, Default)]
+struct Console {
+    open: bool,
+    input: String,
+    log: VecDeque<String>,
+    history: Vec<String>,
+    // how far back through the history the up key has gone
+    recalled: Option<usize>,
+}
+
+impl Console {
+    fn print(&mut self, line: impl Into<String>) {
+        if self.log.len() == LOG_LINES {
+            self.log.pop_front();
+        }
+        self.log.push_back(line.into());
+    }
+}
+
+// how many fish the stress test keeps swimming, replacing any that leave
+#[derive(Resource, Default)]
+struct StressTest {
+    fish: u32,
+}
+
+#[derive(Component)]
+struct ConsolePanel {}
+
+fn spawn_console(mut commands: Commands, theme: Res<Theme>) {
+    super::text_panel(
+        &mut commands,
+        &theme,
+        UiRect {
+            left: Val::Px(10.),
+            top: Val::Px(10.),
+            ..default()
+        },
+        ConsolePanel {},
+    );
+}
+
+fn type_commands(
+    mut keys: ResMut<Input<KeyCode>>,
+    mut chars: EventReader<ReceivedCharacter>,
+    mut console: ResMut<Console>,
+    mut run: EventWriter<RunCommand>,
+) {
+    if !console.open {
+        chars.clear();
+        if keys.just_pressed(KeyCode::Grave) {
+            console.open = true;
+            keys.reset_all();
+        }
+        return;
+    }
+
+    if keys.any_just_pressed([KeyCode::Grave, KeyCode::Escape]) {
+        console.open = false;
+        console.input.clear();
+        chars.clear();
+        keys.reset_all();
+        return;
+    }
+
+    for c in chars.iter() {
+        // the backtick that opened the console comes through as a character too
+        if !c.char.is_control() && c.char != '`' {
+            console.input.push(c.char);
+        }
+    }
+
+    if keys.just_pressed(KeyCode::Back) {
+        console.input.pop();
+    }
+
+    if keys.just_pressed(KeyCode::Up) && !console.history.is_empty() {
+        let back = console
+            .recalled
+            .map_or(0, |i| (i + 1).min(console.history.len() - 1));
+        console.recalled = Some(back);
+        console.input = console.history[console.history.len() - 1 - back].clone();
+    }
+
+    if keys.just_pressed(KeyCode::Return) {
+        let line = std::mem::take(&mut console.input);
+        console.recalled = None;
+        console.print(format!("> {}", line));
+
+        match Command::parse(&line) {
+            Ok(command) => run.send(RunCommand(command)),
+            Err(e) => console.print(e),
+        }
+
+        if !line.trim().is_empty() {
+            console.history.push(line);
+        }
+    }
+
+    // nothing else gets to see the keys while the console has them
+    keys.reset_all();
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_commands(
+    mut commands: Commands,
+    visuals: Res<Visuals>,
+    mut pool: ResMut<FishPool>,
+    mut stress: ResMut<StressTest>,
+    mut run: EventReader<RunCommand>,
+    mut console: ResMut<Console>,
+    state: Res<State<AppState>>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut time: ResMut<Time>,
+    mut score: ResMut<score::Score>,
+    mut changed: EventWriter<events::ScoreChanged>,
+    mut run_stats: ResMut<stats::RunStats>,
+    mut spawner: Option<ResMut<FishSpawnConfig>>,
+    mut next_seed: ResMut<rng::NextSeed>,
+    playfield: Res<Playfield>,
+) {
+    let in_run = state.0 == AppState::Running;
+
+    for RunCommand(command) in run.iter() {
+        let result = match command {
+            Command::Help => Ok(HELP.to_string()),
+            Command::TimeScale(scale) => {
+                time.set_relative_speed(*scale);
+                Ok(format!("time runs at {}x", scale))
+            }
+            Command::Seed(seed) => {
+                next_seed.0 = Some(*seed);
+                if in_run {
+                    next_state.set(AppState::Loading);
+                    Ok(format!("restarting the run with seed {}", seed))
+                } else {
+                    Ok(format!("the next run will use seed {}", seed))
+                }
+            }
+            Command::Stress(fish) => {
+                stress.fish = *fish;
+                if *fish == 0 {
+                    Ok("stress test stopped".to_string())
+                } else {
+                    Ok(format!("keeping {} fish in the lake", fish))
+                }
+            }
+            _ if !in_run => Err("only works during a run".to_string()),
+            Command::Spawn { fish_type, count } => {
+                let wave = spawner.as_ref().map_or(1, |s| s.wave);
+                for _ in 0..*count {
+                    spawn_stray_fish(
+                        &mut commands,
+                        &visuals,
+                        &mut pool,
+                        &playfield,
+                        *fish_type,
+                        wave,
+                    );
+                }
+                Ok(format!("spawned {} {:?}", count, fish_type))
+            }
+            Command::SetScore(val) => {
+                let delta = *val as i32 - score.val as i32;
+                score.val = *val;
+                changed.send(events::ScoreChanged {
+                    delta,
+                    position: None,
+                    combo: 0,
+                });
+                Ok(format!("score set to {}", val))
+            }
+            Command::GameOver => {
+                run_stats.end = Some(stats::RunEnd::EndedEarly);
+                next_state.set(AppState::GameOver);
+                Ok("ending the run".to_string())
+            }
+            Command::Wave(wave) => match spawner.as_mut() {
+                Some(spawner) => {
+                    spawner.wave = *wave;
+                    spawner.spawned = 0;
+                    // earlier waves count as done, so clearing starts again from this one
+                    spawner.cleared = wave - 1;
+                    Ok(format!("now spawning wave {}", wave))
+                }
+                None => Err("there's no fish spawner yet".to_string()),
+            },
+        };
+
+        match result {
+            Ok(message) => console.print(message),
+            Err(e) => console.print(e),
+        }
+    }
+}
+
+// just like the spawner, from either side at any depth clear of the lake bed
+fn spawn_stray_fish(
+    commands: &mut Commands,
+    visuals: &Visuals,
+    pool: &mut FishPool,
+    playfield: &Playfield,
+    fish_type: FishType,
+    wave: u32,
+) {
+    let mut rng = rand::thread_rng();
+    let half_width = playfield.width / 2.;
+    let half_height = playfield.height / 2.;
+
+    let (direction, x) = if rng.gen() {
+        (types::Dir::Forward, -half_width - 20.)
+    } else {
+        (types::Dir::Backward, half_width + 20.)
+    };
+    let depth = rng.gen_range(-half_height + 60.0..150.);
+
+    pool.spawn(
+        commands,
+        enemy::fish_bundle(visuals, fish_type, direction, Vec2::new(x, depth), wave),
+    );
+}
+
+// fish that swim off the far side go back to the pool, and come straight back out of it
+fn keep_lake_stocked(
+    mut commands: Commands,
+    stress: Res<StressTest>,
+    visuals: Res<Visuals>,
+    mut pool: ResMut<FishPool>,
+    playfield: Res<Playfield>,
+    fish_q: Query<&enemy::Fish>,
+) {
+    let swimming = fish_q.iter().filter(|f| f.is_alive()).count() as u32;
+
+    for _ in swimming..stress.fish {
+        // wave 0 is never waited on, so these don't hold up clearing the real waves
+        spawn_stray_fish(
+            &mut commands,
+            &visuals,
+            &mut pool,
+            &playfield,
+            FishType::Basic,
+            0,
+        );
+    }
+}
+
+fn show_console(
+    console: Res<Console>,
+    mut panel_q: Query<(&mut Text, &mut Visibility), With<ConsolePanel>>,
+) {
+    if !console.is_changed() {
+        return;
+    }
+
+    let Ok((mut text, mut visibility)) = panel_q.get_single_mut() else {
+        return;
+    };
+
+    *visibility = super::visibility(console.open);
+
+    let mut lines: Vec<&str> = console.log.iter().map(String::as_str).collect();
+    let prompt = format!("> {}_", console.input);
+    lines.push(&prompt);
+    text.sections[0].value = lines.join("\n");
+}
@@ -0,0 +1,243 @@
+use bevy::{
+    diagnostic::{Diagnostics, FrameTimeDiagnosticsPlugin},
+    prelude::*,
+};
+
+use std::collections::VecDeque;
+
+use super::DevOverlay;
+use crate::ui::theme::Theme;
+
+/// Frame rate and frame time over the last couple of seconds, drawn as bar graphs
+pub struct GraphsPlugin;
+
+impl Plugin for GraphsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(FrameTimeDiagnosticsPlugin)
+            .init_resource::<FrameHistory>()
+            .add_startup_system(spawn_graphs)
+            .add_system(record_frames)
+            .add_system(
+                draw_graphs
+                    .after(record_frames)
+                    .after(super::toggle_overlays),
+            );
+    }
+}
+
+// one bar per frame
+const HISTORY: usize = 120;
+const BAR_WIDTH: f32 = 2.;
+const GRAPH_HEIGHT: f32 = 50.;
+
+// a frame that takes longer than this has missed 60 fps
+const TARGET_FRAME_MS: f32 = 1000. / 60.;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Graph {
+    Fps,
+    FrameTime,
+}
+
+impl Graph {
+    const ALL: [Graph; 2] = [Graph::Fps, Graph::FrameTime];
+
+    // the value that fills the graph to the top
+    fn ceiling(self) -> f32 {
+        match self {
+            Graph::Fps => 120.,
+            Graph::FrameTime => TARGET_FRAME_MS * 2.,
+        }
+    }
+
+    fn label(self, history: &VecDeque<f32>) -> String {
+        let latest = history.back().copied().unwrap_or(0.);
+        let worst = match self {
+            Graph::Fps => history.iter().copied().reduce(f32::min),
+            Graph::FrameTime => history.iter().copied().reduce(f32::max),
+        }
+        .unwrap_or(0.);
+
+        match self {
+            Graph::Fps => format!("FPS {:.0} (low {:.0})", latest, worst),
+            Graph::FrameTime => format!("Frame {:.1} ms (high {:.1})", latest, worst),
+        }
+    }
+
+    fn is_slow(self, value: f32) -> bool {
+        match self {
+            Graph::Fps => value < 60. - 1.,
+            Graph::FrameTime => value > TARGET_FRAME_MS + 1.,
+        }
+    }
+}
+
+#[derive(Resource)]
+struct FrameHistory {
+    fps: VecDeque<f32>,
+    frame_time: VecDeque<f32>,
+}
+
+impl Default for FrameHistory {
+    fn default() -> Self {
+        FrameHistory {
+            fps: VecDeque::with_capacity(HISTORY),
+            frame_time: VecDeque::with_capacity(HISTORY),
+        }
+    }
+}
+
+impl FrameHistory {
+    fn of(&self, graph: Graph) -> &VecDeque<f32> {
+        match graph {
+            Graph::Fps => &self.fps,
+            Graph::FrameTime => &self.frame_time,
+        }
+    }
+}
+
+#[derive(Component)]
+struct GraphPanel {}
+
+#[derive(Component)]
+struct GraphLabel(Graph);
+
+// bar `index` counts back from the newest frame on the right
+#[derive(Component)]
+struct GraphBar {
+    graph: Graph,
+    index: usize,
+}
+
+fn spawn_graphs(mut commands: Commands, theme: Res<Theme>) {
+    let style = TextStyle {
+        font: theme.font.clone(),
+        font_size: theme.small_size,
+        color: Color::WHITE,
+    };
+
+    commands
+        .spawn((
+            GraphPanel {},
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        right: Val::Px(10.),
+                        bottom: Val::Px(10.),
+                        ..default()
+                    },
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(6.)),
+                    gap: Size::height(Val::Px(4.)),
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.6).into(),
+                visibility: Visibility::Hidden,
+                z_index: ZIndex::Global(100),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            for graph in Graph::ALL {
+                parent.spawn((
+                    GraphLabel(graph),
+                    TextBundle::from_section("", style.clone()),
+                ));
+
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            size: Size::new(
+                                Val::Px(BAR_WIDTH * HISTORY as f32),
+                                Val::Px(GRAPH_HEIGHT),
+                            ),
+                            align_items: AlignItems::FlexEnd,
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|bars| {
+                        for index in (0..HISTORY).rev() {
+                            bars.spawn((
+                                GraphBar { graph, index },
+                                NodeBundle {
+                                    style: Style {
+                                        size: Size::new(Val::Px(BAR_WIDTH), Val::Px(0.)),
+                                        ..default()
+                                    },
+                                    ..default()
+                                },
+                            ));
+                        }
+                    });
+            }
+        });
+}
+
+fn record_frames(diagnostics: Res<Diagnostics>, mut history: ResMut<FrameHistory>) {
+    let latest = |id| {
+        diagnostics
+            .get(id)
+            .and_then(|d| d.value())
+            .map(|v| v as f32)
+    };
+
+    let history = &mut *history;
+    let samples = [
+        (&mut history.fps, latest(FrameTimeDiagnosticsPlugin::FPS)),
+        (
+            &mut history.frame_time,
+            latest(FrameTimeDiagnosticsPlugin::FRAME_TIME),
+        ),
+    ];
+
+    for (values, sample) in samples {
+        let Some(sample) = sample else {
+            continue;
+        };
+
+        if values.len() == HISTORY {
+            values.pop_front();
+        }
+        values.push_back(sample);
+    }
+}
+
+fn draw_graphs(
+    overlay: Res<DevOverlay>,
+    history: Res<FrameHistory>,
+    mut panel_q: Query<&mut Visibility, With<GraphPanel>>,
+    mut label_q: Query<(&GraphLabel, &mut Text)>,
+    mut bar_q: Query<(&GraphBar, &mut Style, &mut BackgroundColor)>,
+) {
+    if overlay.is_changed() {
+        for mut visibility in panel_q.iter_mut() {
+            *visibility = super::visibility(overlay.graphs);
+        }
+    }
+
+    if !overlay.graphs {
+        return;
+    }
+
+    for (label, mut text) in label_q.iter_mut() {
+        text.sections[0].value = label.0.label(history.of(label.0));
+    }
+
+    for (bar, mut style, mut color) in bar_q.iter_mut() {
+        let values = history.of(bar.graph);
+        let value = values
+            .len()
+            .checked_sub(bar.index + 1)
+            .map_or(0., |i| values[i]);
+
+        let fill = (value / bar.graph.ceiling()).clamp(0., 1.);
+        style.size.height = Val::Px(fill * GRAPH_HEIGHT);
+        color.0 = if bar.graph.is_slow(value) {
+            Color::ORANGE_RED
+        } else {
+            Color::LIME_GREEN
+        };
+    }
+}
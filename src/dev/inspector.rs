@@ -0,0 +1,112 @@
+use bevy::prelude::*;
+
+use std::fmt::Write;
+
+use super::DevOverlay;
+use crate::enemy;
+use crate::ui::theme::Theme;
+
+/// A running count of entities and assets, and a line for every fish in the lake
+pub struct InspectorPlugin;
+
+impl Plugin for InspectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(InspectorRefresh(Timer::from_seconds(
+            REFRESH_SECONDS,
+            TimerMode::Repeating,
+        )))
+        .add_startup_system(spawn_inspector)
+        .add_system(update_inspector.after(super::toggle_overlays));
+    }
+}
+
+// often enough to follow along, slow enough to read
+const REFRESH_SECONDS: f32 = 0.25;
+// past this many fish the rest are just counted
+const MAX_FISH_LINES: usize = 20;
+
+#[derive(Resource)]
+struct InspectorRefresh(Timer);
+
+#[derive(Component)]
+struct InspectorPanel {}
+
+fn spawn_inspector(mut commands: Commands, theme: Res<Theme>) {
+    super::text_panel(
+        &mut commands,
+        &theme,
+        UiRect {
+            left: Val::Px(10.),
+            top: Val::Px(60.),
+            ..default()
+        },
+        InspectorPanel {},
+    );
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_inspector(
+    time: Res<Time>,
+    overlay: Res<DevOverlay>,
+    mut refresh: ResMut<InspectorRefresh>,
+    meshes: Res<Assets<Mesh>>,
+    materials: Res<Assets<ColorMaterial>>,
+    pool: Res<enemy::FishPool>,
+    entity_q: Query<Entity>,
+    fish_q: Query<(Entity, &enemy::Fish, &Transform)>,
+    spawner: Option<Res<enemy::FishSpawnConfig>>,
+    mut panel_q: Query<(&mut Text, &mut Visibility), With<InspectorPanel>>,
+) {
+    let Ok((mut text, mut visibility)) = panel_q.get_single_mut() else {
+        return;
+    };
+
+    if overlay.is_changed() {
+        *visibility = super::visibility(overlay.inspector);
+    }
+
+    // real time, so it keeps up while the game is paused
+    refresh.0.tick(time.raw_delta());
+    if !overlay.inspector || !(refresh.0.just_finished() || overlay.is_changed()) {
+        return;
+    }
+
+    let mut report = format!("Entities: {}\n", entity_q.iter().count());
+    // these should stay flat however many fish come and go
+    let _ = writeln!(
+        report,
+        "Meshes: {}  Materials: {}  Spare fish: {}",
+        meshes.len(),
+        materials.len(),
+        pool.spare()
+    );
+
+    if let Some(spawner) = spawner {
+        let _ = writeln!(
+            report,
+            "Wave {} ({} spawned, {} cleared)",
+            spawner.wave, spawner.spawned, spawner.cleared
+        );
+    }
+
+    let _ = writeln!(report, "Fish: {}", fish_q.iter().len());
+    for (entity, fish, transform) in fish_q.iter().take(MAX_FISH_LINES) {
+        let _ = writeln!(
+            report,
+            "  {:?} {:?} wave {} {} at ({:.0}, {:.0})",
+            entity,
+            fish.fish_type,
+            fish.wave,
+            if fish.is_alive() { "alive" } else { "dead" },
+            transform.translation.x,
+            transform.translation.y,
+        );
+    }
+
+    let hidden = fish_q.iter().len().saturating_sub(MAX_FISH_LINES);
+    if hidden > 0 {
+        let _ = writeln!(report, "  and {} more", hidden);
+    }
+
+    text.sections[0].value = report;
+}
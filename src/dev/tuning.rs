@@ -0,0 +1,162 @@
+use bevy::prelude::*;
+
+use std::time::Duration;
+
+use super::DevOverlay;
+use crate::enemy::FishTuning;
+use crate::player::PlayerTuning;
+use crate::ui::theme::Theme;
+
+/// Changes spawn and speed values while playing.
+/// `[` and `]` pick a value, `-` and `=` turn it down and up.
+pub struct TuningPlugin;
+
+impl Plugin for TuningPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SelectedValue>()
+            .add_startup_system(spawn_tuning)
+            .add_system(adjust_values.after(super::toggle_overlays))
+            .add_system(show_values.after(adjust_values));
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Tunable {
+    SpawnInterval,
+    FishSpeed,
+    WalkSpeed,
+    BombSinkSpeed,
+}
+
+impl Tunable {
+    const ALL: [Tunable; 4] = [
+        Tunable::SpawnInterval,
+        Tunable::FishSpeed,
+        Tunable::WalkSpeed,
+        Tunable::BombSinkSpeed,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Tunable::SpawnInterval => "Spawn interval (s)",
+            Tunable::FishSpeed => "Fish speed",
+            Tunable::WalkSpeed => "Walk speed",
+            Tunable::BombSinkSpeed => "Bomb sink speed",
+        }
+    }
+
+    fn value(self, fish: &FishTuning, player: &PlayerTuning) -> f32 {
+        match self {
+            Tunable::SpawnInterval => fish.spawn_interval.as_secs_f32(),
+            Tunable::FishSpeed => fish.speed,
+            Tunable::WalkSpeed => player.walk_speed,
+            Tunable::BombSinkSpeed => player.bomb_sink_speed,
+        }
+    }
+
+    // how far one press moves the value, and the lowest it can go
+    fn step_and_min(self) -> (f32, f32) {
+        match self {
+            Tunable::SpawnInterval => (0.1, 0.1),
+            _ => (10., 0.),
+        }
+    }
+
+    fn nudge(self, fish: &mut FishTuning, player: &mut PlayerTuning, steps: f32) {
+        let (step, min) = self.step_and_min();
+        let value = (self.value(fish, player) + step * steps).max(min);
+
+        match self {
+            Tunable::SpawnInterval => fish.spawn_interval = Duration::from_secs_f32(value),
+            Tunable::FishSpeed => fish.speed = value,
+            Tunable::WalkSpeed => player.walk_speed = value,
+            Tunable::BombSinkSpeed => player.bomb_sink_speed = value,
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+struct SelectedValue(usize);
+
+#[derive(Component)]
+struct TuningPanel {}
+
+fn spawn_tuning(mut commands: Commands, theme: Res<Theme>) {
+    super::text_panel(
+        &mut commands,
+        &theme,
+        UiRect {
+            left: Val::Px(10.),
+            bottom: Val::Px(10.),
+            ..default()
+        },
+        TuningPanel {},
+    );
+}
+
+fn adjust_values(
+    keys: Res<Input<KeyCode>>,
+    overlay: Res<DevOverlay>,
+    mut selected: ResMut<SelectedValue>,
+    mut fish: ResMut<FishTuning>,
+    mut player: ResMut<PlayerTuning>,
+) {
+    if !overlay.tuning {
+        return;
+    }
+
+    let count = Tunable::ALL.len();
+    if keys.just_pressed(KeyCode::LBracket) {
+        selected.0 = (selected.0 + count - 1) % count;
+    }
+    if keys.just_pressed(KeyCode::RBracket) {
+        selected.0 = (selected.0 + 1) % count;
+    }
+
+    let steps = if keys.just_pressed(KeyCode::Equals) {
+        1.
+    } else if keys.just_pressed(KeyCode::Minus) {
+        -1.
+    } else {
+        return;
+    };
+
+    Tunable::ALL[selected.0].nudge(&mut fish, &mut player, steps);
+}
+
+fn show_values(
+    overlay: Res<DevOverlay>,
+    selected: Res<SelectedValue>,
+    fish: Res<FishTuning>,
+    player: Res<PlayerTuning>,
+    mut panel_q: Query<(&mut Text, &mut Visibility), With<TuningPanel>>,
+) {
+    let Ok((mut text, mut visibility)) = panel_q.get_single_mut() else {
+        return;
+    };
+
+    if overlay.is_changed() {
+        *visibility = super::visibility(overlay.tuning);
+    }
+
+    let changed = overlay.is_changed() || selected.is_changed();
+    if !overlay.tuning || !(changed || fish.is_changed() || player.is_changed()) {
+        return;
+    }
+
+    let lines: Vec<String> = Tunable::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, tunable)| {
+            let marker = if i == selected.0 { ">" } else { " " };
+            format!(
+                "{} {}: {:.1}",
+                marker,
+                tunable.name(),
+                tunable.value(&fish, &player)
+            )
+        })
+        .collect();
+
+    text.sections[0].value = format!("Tuning  [ ] pick  - = change\n{}", lines.join("\n"));
+}
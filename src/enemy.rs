@@ -4,64 +4,188 @@ use bevy_rapier2d::prelude::*;
 use rand::Rng;
 use std::time::Duration;
 
+use crate::current;
+use crate::events;
+use crate::layers;
 use crate::level;
+use crate::particles;
 use crate::player;
-use crate::state;
+use crate::playfield::Playfield;
+use crate::pool::Pool;
+use crate::rng;
+use crate::run::{self, RunApp};
+use crate::sets::GameSet;
+use crate::timestep;
+use crate::transition;
 use crate::types;
+use crate::visuals::Visuals;
 
 pub struct EnemyPlugin;
 
 impl Plugin for EnemyPlugin {
     fn build(&self, app: &mut App) {
-        app.add_startup_system(setup_fish_spawning)
+        app.init_resource::<FishTuning>()
+            .init_run_pool::<FishPool>()
+            .init_run_resource::<FishSpawnConfig>()
             .add_systems(
-                (spawn_fish, fish_collision, fish_swim).in_set(OnUpdate(state::AppState::Running)),
-            )
-            .add_system(clean_up_fish.in_schedule(OnEnter(state::AppState::GameOver)));
+                (
+                    spawn_fish
+                        .run_if(transition::idle)
+                        .in_set(GameSet::Movement),
+                    fish_swim
+                        .in_set(GameSet::Movement)
+                        .before(current::apply_currents),
+                    fish_collision.in_set(GameSet::Combat),
+                    check_wave_cleared.in_set(GameSet::Scoring),
+                )
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            );
     }
 }
 
+// how many fish make up a wave
+const WAVE_SIZE: u32 = 10;
+
+/// Fish come in waves, a wave is cleared once none of its fish are left swimming
 #[derive(Resource)]
-struct FishSpawnConfig {
-    timer: Timer,
+pub struct FishSpawnConfig {
+    pub timer: Timer,
+    /// The wave being spawned, starting from 1
+    pub wave: u32,
+    /// Fish of that wave spawned so far
+    pub spawned: u32,
+    /// The last wave that was cleared, 0 before any have been
+    pub cleared: u32,
+}
+
+impl FromWorld for FishSpawnConfig {
+    fn from_world(world: &mut World) -> Self {
+        let tuning = world.resource::<FishTuning>();
+
+        FishSpawnConfig {
+            timer: Timer::new(tuning.spawn_interval, TimerMode::Repeating),
+            wave: 1,
+            spawned: 0,
+            cleared: 0,
+        }
+    }
 }
 
-#[derive(Debug)]
+/// How fast fish turn up and swim, kept apart so they can be tuned while playing
+#[derive(Resource)]
+pub struct FishTuning {
+    pub spawn_interval: Duration,
+    pub speed: f32,
+}
+
+impl Default for FishTuning {
+    fn default() -> Self {
+        FishTuning {
+            spawn_interval: Duration::from_secs(2),
+            speed: 100.,
+        }
+    }
+}
+
+/// Fish that have been caught or have swum off, waiting to be spawned again
+pub type FishPool = Pool<(
+    Fish,
+    current::Drift,
+    timestep::Interpolated,
+    particles::Trail,
+)>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum FishType {
     Basic,
     Turtle,
 }
 
+impl FishType {
+    pub const ALL: [FishType; 2] = [FishType::Basic, FishType::Turtle];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FishType::Basic => "Fish",
+            FishType::Turtle => "Turtles",
+        }
+    }
+}
+
 #[derive(Component)]
 pub struct Fish {
     pub fish_type: FishType,
-    direction: types::Dir,
+    pub direction: types::Dir,
+    pub wave: u32,
+}
+
+impl Fish {
+    pub fn is_alive(&self) -> bool {
+        !matches!(self.direction, types::Dir::Up)
+    }
+
+    /// How much the water moves the fish about
+    pub fn drift(&self) -> current::Drift {
+        // live fish swim against the current, so it only nudges them
+        current::Drift::new(if self.is_alive() { 0.4 } else { 1. })
+    }
 }
 
-fn setup_fish_spawning(mut commands: Commands) {
-    commands.insert_resource(FishSpawnConfig {
-        timer: Timer::new(Duration::from_secs(2), TimerMode::Repeating),
-    })
+/// Everything a fish is made of, swimming in `direction` from `position`
+pub fn fish_bundle(
+    visuals: &Visuals,
+    fish_type: FishType,
+    direction: types::Dir,
+    position: Vec2,
+    wave: u32,
+) -> impl Bundle {
+    let fish = Fish {
+        fish_type,
+        direction,
+        wave,
+    };
+
+    let drift = fish.drift();
+
+    (
+        MaterialMesh2dBundle {
+            mesh: visuals.fish_mesh.clone(),
+            material: visuals.fish_material(fish_type),
+            transform: Transform::from_xyz(position.x, position.y, 1.),
+            ..default()
+        },
+        fish,
+        drift,
+        Collider::cuboid(10., 5.),
+        layers::fish(),
+        timestep::Interpolated::default(),
+        run::RunScoped {},
+    )
 }
 
+#[allow(clippy::too_many_arguments)]
 fn spawn_fish(
     mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    time: Res<Time>,
+    visuals: Res<Visuals>,
+    mut pool: ResMut<FishPool>,
+    fixed_time: Res<FixedTime>,
     mut config: ResMut<FishSpawnConfig>,
-    query: Query<&Window>,
+    tuning: Res<FishTuning>,
+    mut rng: ResMut<rng::GameRng>,
+    playfield: Res<Playfield>,
 ) {
-    let window = query.single();
-    let window_width = window.width() / 2.;
-    let window_height = window.height() / 2.;
+    let half_width = playfield.width / 2.;
+    let half_height = playfield.height / 2.;
 
-    config.timer.tick(time.delta());
+    if config.timer.duration() != tuning.spawn_interval {
+        config.timer.set_duration(tuning.spawn_interval);
+    }
+    config.timer.tick(fixed_time.period);
 
     if config.timer.just_finished() {
         // get a random depth to spawn at
-        let mut rng = rand::thread_rng();
-        let rand_depth = rng.gen_range(-1. * window_height..150.);
+        // stay clear of the lake bed
+        let rand_depth = rng.gen_range(-half_height + 60.0..150.);
 
         // spawn on or left or right side randomly
         let rand_dir = rng.gen_range(0..2);
@@ -73,8 +197,8 @@ fn spawn_fish(
         };
 
         let starting_x = match direction {
-            types::Dir::Forward => (window_width * -1.) - 20.,
-            _ => window_width + 20.,
+            types::Dir::Forward => -half_width - 20.,
+            _ => half_width + 20.,
         };
 
         // eventually spawn a random type of fish here
@@ -87,92 +211,114 @@ fn spawn_fish(
             FishType::Basic
         };
 
-        let color = match fish_type {
-            FishType::Basic => Color::hex("fc6a03").unwrap(),
-            FishType::Turtle => Color::hex("3cb043").unwrap(),
-        };
-
         // spawn fish on a timer
-        commands.spawn((
-            MaterialMesh2dBundle {
-                mesh: meshes
-                    .add(shape::Quad::new(Vec2::new(15., 10.)).into())
-                    .into(),
-                material: materials.add(ColorMaterial::from(color)),
-                transform: Transform::from_xyz(starting_x, rand_depth, 1.),
-                ..default()
-            },
-            Fish {
+        pool.spawn(
+            &mut commands,
+            fish_bundle(
+                &visuals,
                 fish_type,
                 direction,
-            },
-            Collider::cuboid(10., 5.),
-        ));
+                Vec2::new(starting_x, rand_depth),
+                config.wave,
+            ),
+        );
+
+        config.spawned += 1;
+        if config.spawned == WAVE_SIZE {
+            config.spawned = 0;
+            config.wave += 1;
+        }
     }
 }
 
+fn check_wave_cleared(
+    mut config: ResMut<FishSpawnConfig>,
+    fish_q: Query<&Fish>,
+    mut cleared: EventWriter<events::WaveCleared>,
+) {
+    let next = config.cleared + 1;
+
+    // a wave can't be cleared before all of it has been spawned
+    if next >= config.wave {
+        return;
+    }
+
+    if fish_q.iter().any(|f| f.wave == next && f.is_alive()) {
+        return;
+    }
+
+    config.cleared = next;
+    cleared.send(events::WaveCleared { wave: next });
+}
+
 fn fish_swim(
-    time: Res<Time>,
+    fixed_time: Res<FixedTime>,
+    tuning: Res<FishTuning>,
     mut query: Query<(&Fish, &mut Transform), Without<level::Ground>>,
     ground_q: Query<&Transform, With<level::Ground>>,
 ) {
     let ground_trans = ground_q.single();
+    let distance = tuning.speed * fixed_time.period.as_secs_f32();
 
     for (fish, mut transform) in query.iter_mut() {
         match fish.direction {
-            types::Dir::Forward => transform.translation.x += 50. * time.delta_seconds() * 2.,
-            types::Dir::Backward => transform.translation.x -= 50. * time.delta_seconds() * 2.,
+            types::Dir::Forward => transform.translation.x += distance,
+            types::Dir::Backward => transform.translation.x -= distance,
             types::Dir::Up => {
                 if transform.translation.y <= ground_trans.translation.y {
-                    transform.translation.y += 50. * time.delta_seconds() * 2.
+                    transform.translation.y += distance
                 }
             }
         }
     }
 }
 
-pub fn get_score_for_fish_type(ft: &FishType) -> u32 {
-    match ft {
-        FishType::Basic => 100,
-        _ => 0,
-    }
-}
-
 fn fish_collision(
-    rap_ctx: Res<RapierContext>,
-    explosion_q: Query<Entity, With<player::Explosion>>,
-    mut fish_q: Query<&mut Fish>,
-    mut next_state: ResMut<NextState<state::AppState>>,
+    mut collisions: EventReader<CollisionEvent>,
+    mut explosion_q: Query<&mut player::Explosion>,
+    mut fish_q: Query<(&mut Fish, &mut current::Drift, &Transform)>,
+    mut killed: EventWriter<events::FishKilled>,
+    mut turtle_hit: EventWriter<events::TurtleHit>,
 ) {
-    for explosion_entity in explosion_q.iter() {
-        for contact_pair in rap_ctx.contacts_with(explosion_entity) {
-            let other_entity = if contact_pair.collider1() == explosion_entity {
-                contact_pair.collider2()
-            } else {
-                contact_pair.collider1()
-            };
-
-            if let Ok(mut fish) = fish_q.get_mut(other_entity) {
-                info!("killing fish of type {:?}", fish.fish_type);
-
-                match fish.fish_type {
-                    FishType::Basic => {
-                        fish.direction = types::Dir::Up;
-                    }
-                    FishType::Turtle => {
-                        // end the game here
-                        next_state.set(state::AppState::GameOver);
-                    }
-                }
-            } else {
-                warn!("couldn't get the fish");
-            }
+    for event in collisions.iter() {
+        let Some((explosion_entity, fish_entity)) =
+            layers::started(event, |e| explosion_q.contains(e))
+        else {
+            continue;
+        };
+
+        let (Ok(mut explosion), Ok((mut fish, mut drift, transform))) = (
+            explosion_q.get_mut(explosion_entity),
+            fish_q.get_mut(fish_entity),
+        ) else {
+            continue;
+        };
+
+        // fish already floating up can drift into another explosion
+        if !fish.is_alive() {
+            continue;
         }
-    }
-}
 
-fn clean_up_fish(mut commands: Commands, fish_q: Query<Entity, With<Fish>>) {
-    for e in fish_q.iter() {
-        commands.entity(e).despawn_recursive();
+        debug!("killing fish of type {:?}", fish.fish_type);
+        let position = transform.translation.truncate();
+
+        killed.send(events::FishKilled {
+            fish: fish_entity,
+            fish_type: fish.fish_type,
+            position,
+            first_for_bomb: !explosion.hit,
+        });
+        explosion.hit = true;
+
+        match fish.fish_type {
+            FishType::Basic => {
+                fish.direction = types::Dir::Up;
+                // dead fish go wherever the water takes them
+                drift.response = 1.;
+            }
+            FishType::Turtle => {
+                turtle_hit.send(events::TurtleHit { position });
+            }
+        }
     }
 }
@@ -0,0 +1,74 @@
+use bevy::prelude::*;
+
+use crate::enemy::FishType;
+
+/// Registers the gameplay events.
+/// Systems that run the game send these, and scoring, stats and the rest each react on their own.
+pub struct EventsPlugin;
+
+impl Plugin for EventsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<FishKilled>()
+            .add_event::<FishCollected>()
+            .add_event::<FishEscaped>()
+            .add_event::<TurtleHit>()
+            .add_event::<BombDropped>()
+            .add_event::<BombSplashed>()
+            .add_event::<BombDetonated>()
+            .add_event::<ScoreChanged>()
+            .add_event::<WaveCleared>();
+    }
+}
+
+/// A fish was caught in an explosion, turtles included
+pub struct FishKilled {
+    pub fish: Entity,
+    pub fish_type: FishType,
+    pub position: Vec2,
+    /// Whether it's the first fish this explosion has killed
+    pub first_for_bomb: bool,
+}
+
+/// The player picked up a dead fish
+pub struct FishCollected {
+    pub fish: Entity,
+    pub fish_type: FishType,
+    pub position: Vec2,
+}
+
+/// A live fish swam off the edge of the lake
+pub struct FishEscaped {
+    pub fish_type: FishType,
+}
+
+/// An explosion killed a protected turtle, which ends the run
+pub struct TurtleHit {
+    pub position: Vec2,
+}
+
+pub struct BombDropped {
+    pub position: Vec2,
+}
+
+/// A dropped bomb hit the water
+pub struct BombSplashed {
+    pub position: Vec2,
+}
+
+pub struct BombDetonated {
+    pub position: Vec2,
+}
+
+/// The score went up or down
+pub struct ScoreChanged {
+    pub delta: i32,
+    /// Where in the world the points came from, if anywhere
+    pub position: Option<Vec2>,
+    /// How many fish in a row have been brought in quickly, 1 when it isn't a combo
+    pub combo: u32,
+}
+
+/// Every fish in a wave has been killed, collected or has escaped
+pub struct WaveCleared {
+    pub wave: u32,
+}
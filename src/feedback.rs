@@ -0,0 +1,171 @@
+use bevy::prelude::*;
+
+use crate::events;
+use crate::run::RunScoped;
+use crate::sets::GameSet;
+use crate::ui::theme::Theme;
+
+pub struct FeedbackPlugin;
+
+impl Plugin for FeedbackPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(spawn_flash)
+            .add_systems((score_popups, turtle_popups, flash_on_penalty).in_set(GameSet::Ui))
+            .add_systems((float_popups, fade_flash).in_set(GameSet::Ui));
+    }
+}
+
+const POPUP_LIFETIME: f32 = 1.;
+const POPUP_RISE_SPEED: f32 = 40.;
+// popups start a little above whatever they're about
+const POPUP_OFFSET: f32 = 20.;
+
+const POINTS_COLOR: Color = Color::GOLD;
+const COMBO_COLOR: Color = Color::ORANGE;
+const PENALTY_COLOR: Color = Color::RED;
+
+const FLASH_ALPHA: f32 = 0.35;
+const FLASH_FADE_SPEED: f32 = 1.5;
+
+/// Text in the world that floats up and fades away
+#[derive(Component)]
+struct Popup {
+    age: f32,
+}
+
+// a red tint over the whole screen when something goes wrong
+#[derive(Component)]
+struct PenaltyFlash {}
+
+fn spawn_popup(commands: &mut Commands, position: Vec2, sections: Vec<TextSection>) {
+    commands.spawn((
+        Popup { age: 0. },
+        RunScoped {},
+        Text2dBundle {
+            text: Text::from_sections(sections).with_alignment(TextAlignment::Center),
+            transform: Transform::from_xyz(position.x, position.y + POPUP_OFFSET, 5.),
+            ..default()
+        },
+    ));
+}
+
+fn popup_style(theme: &Theme, font_size: f32, color: Color) -> TextStyle {
+    TextStyle {
+        font: theme.font.clone(),
+        font_size,
+        color,
+    }
+}
+
+fn score_popups(
+    mut commands: Commands,
+    theme: Res<Theme>,
+    mut changed: EventReader<events::ScoreChanged>,
+) {
+    for change in changed.iter() {
+        let Some(position) = change.position else {
+            continue;
+        };
+
+        let (text, color) = if change.delta < 0 {
+            (format!("{}", change.delta), PENALTY_COLOR)
+        } else {
+            (format!("+{}", change.delta), POINTS_COLOR)
+        };
+
+        let mut sections = vec![TextSection::new(
+            text,
+            popup_style(&theme, theme.heading_size, color),
+        )];
+
+        if change.combo > 1 {
+            sections.push(TextSection::new(
+                format!("\nCombo x{}", change.combo),
+                popup_style(&theme, theme.small_size, COMBO_COLOR),
+            ));
+        }
+
+        spawn_popup(&mut commands, position, sections);
+    }
+}
+
+fn turtle_popups(
+    mut commands: Commands,
+    theme: Res<Theme>,
+    mut turtle_hit: EventReader<events::TurtleHit>,
+) {
+    for hit in turtle_hit.iter() {
+        spawn_popup(
+            &mut commands,
+            hit.position,
+            vec![TextSection::new(
+                "Turtle!",
+                popup_style(&theme, theme.heading_size, PENALTY_COLOR),
+            )],
+        );
+    }
+}
+
+fn float_popups(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut popup_q: Query<(Entity, &mut Popup, &mut Transform, &mut Text)>,
+) {
+    for (entity, mut popup, mut transform, mut text) in popup_q.iter_mut() {
+        popup.age += time.delta_seconds();
+        if popup.age >= POPUP_LIFETIME {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        transform.translation.y += POPUP_RISE_SPEED * time.delta_seconds();
+
+        let alpha = 1. - popup.age / POPUP_LIFETIME;
+        for section in text.sections.iter_mut() {
+            section.style.color.set_a(alpha);
+        }
+    }
+}
+
+fn spawn_flash(mut commands: Commands) {
+    commands.spawn((
+        PenaltyFlash {},
+        NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            background_color: PENALTY_COLOR.with_a(0.).into(),
+            ..default()
+        },
+    ));
+}
+
+fn flash_on_penalty(
+    mut changed: EventReader<events::ScoreChanged>,
+    mut turtle_hit: EventReader<events::TurtleHit>,
+    mut flash_q: Query<&mut BackgroundColor, With<PenaltyFlash>>,
+) {
+    let penalties = changed.iter().filter(|c| c.delta < 0).count() + turtle_hit.iter().count();
+    if penalties == 0 {
+        return;
+    }
+
+    if let Ok(mut color) = flash_q.get_single_mut() {
+        color.0.set_a(FLASH_ALPHA);
+    }
+}
+
+fn fade_flash(time: Res<Time>, mut flash_q: Query<&mut BackgroundColor, With<PenaltyFlash>>) {
+    let Ok(mut color) = flash_q.get_single_mut() else {
+        return;
+    };
+
+    let alpha = color.0.a();
+    if alpha > 0. {
+        color
+            .0
+            .set_a((alpha - FLASH_FADE_SPEED * time.delta_seconds()).max(0.));
+    }
+}
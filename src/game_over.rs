@@ -1,14 +1,46 @@
 use bevy::prelude::*;
 
+use crate::enemy;
+use crate::events;
+use crate::highscore;
+use crate::menu;
+use crate::replay;
+use crate::score;
+use crate::sets::GameSet;
 use crate::state;
+use crate::stats;
+use crate::ui::{
+    focus,
+    theme::{TextRole, Theme, UiText},
+    widgets,
+};
 
 pub struct GameOverPlugin;
 
 impl Plugin for GameOverPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(spawn_game_over.in_schedule(OnEnter(state::AppState::GameOver)))
-            .add_system(play_again_button.in_set(OnUpdate(state::AppState::GameOver)))
-            .add_system(despawn_game_over.in_schedule(OnExit(state::AppState::GameOver)));
+        app.add_system(
+            end_run_on_turtle_hit
+                .in_set(GameSet::Scoring)
+                .in_set(OnUpdate(state::AppState::Running)),
+        )
+        .add_system(
+            spawn_game_over
+                .after(highscore::offer_name_entry)
+                .in_schedule(OnEnter(state::AppState::GameOver)),
+        )
+        .add_systems(
+            (
+                game_over_buttons.after(focus::FocusSet),
+                lock_focus_while_typing
+                    .after(highscore::type_name)
+                    .before(focus::FocusSet),
+                update_name_entry,
+            )
+                .in_set(OnUpdate(state::AppState::GameOver)),
+        )
+        .add_system(unlock_focus.in_schedule(OnExit(state::AppState::GameOver)))
+        .add_system(despawn_game_over.in_schedule(OnExit(state::AppState::GameOver)));
     }
 }
 
@@ -16,61 +48,104 @@ impl Plugin for GameOverPlugin {
 struct GameOverMenu {}
 
 #[derive(Component)]
-struct RestartButton {}
-
-fn spawn_game_over(mut commands: Commands, asset_server: Res<AssetServer>) {
-    commands
-        .spawn((
-            GameOverMenu {},
-            NodeBundle {
-                style: Style {
-                    size: Size::width(Val::Percent(100.)),
-                    flex_direction: FlexDirection::Column,
-                    justify_content: JustifyContent::Center,
-                    align_items: AlignItems::Center,
-                    ..default()
-                },
-                ..default()
-            },
-        ))
-        .with_children(|parent| {
-            parent.spawn(TextBundle::from_section(
-                "Game Over",
-                TextStyle {
-                    font: asset_server.load("fonts/OpenSans.ttf"),
-                    font_size: 45.,
-                    color: Color::BLACK,
-                },
+enum GameOverButton {
+    PlayAgain,
+    WatchReplay,
+    Leaderboard,
+    MainMenu,
+}
+
+#[derive(Component)]
+struct NameEntryText {}
+
+fn name_entry_label(entry: &highscore::NameEntry) -> String {
+    match entry.saved_rank {
+        Some(rank) => format!("Saved to the leaderboard at #{}", rank + 1),
+        None => format!("New high score! Type your name: {}_", entry.name),
+    }
+}
+
+fn format_duration(duration: std::time::Duration) -> String {
+    let secs = duration.as_secs();
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+/// Hitting a turtle is the one way a run can be lost
+pub fn end_run_on_turtle_hit(
+    mut turtle_hit: EventReader<events::TurtleHit>,
+    mut next_state: ResMut<NextState<state::AppState>>,
+) {
+    if turtle_hit.iter().count() > 0 {
+        next_state.set(state::AppState::GameOver);
+    }
+}
+
+fn spawn_game_over(
+    mut commands: Commands,
+    theme: Res<Theme>,
+    score: Res<score::Score>,
+    stats: Res<stats::RunStats>,
+    name_entry: Res<highscore::NameEntry>,
+) {
+    let mut lines = vec![];
+
+    for fish_type in enemy::FishType::ALL {
+        let species = stats.species(fish_type);
+        lines.push(format!(
+            "{}: {} killed, {} collected",
+            fish_type.name(),
+            species.killed,
+            species.collected
+        ));
+    }
+
+    lines.push(format!("Escaped: {}", stats.escaped));
+    lines.push(format!("Bombs used: {}", stats.bombs_used));
+    lines.push(format!("Accuracy: {:.0}%", stats.accuracy()));
+    lines.push(format!("Time: {}", format_duration(stats.duration)));
+
+    let menu = widgets::screen(&mut commands, &theme, GameOverMenu {});
+
+    commands.entity(menu).with_children(|parent| {
+        widgets::title(parent, "Game Over");
+
+        if let Some(end) = stats.end {
+            widgets::label(parent, end.description());
+        }
+
+        widgets::title(parent, format!("Score: {}", score.val));
+
+        if stats.new_best {
+            parent.spawn(widgets::text_bundle(UiText::highlighted(
+                TextRole::Heading,
+                "New personal best!",
+            )));
+        }
+
+        widgets::panel(parent, &theme, |parent| {
+            for line in lines {
+                widgets::label(parent, line);
+            }
+        });
+
+        if name_entry.active || name_entry.saved_rank.is_some() {
+            parent.spawn((
+                NameEntryText {},
+                widgets::text_bundle(UiText::new(TextRole::Body, name_entry_label(&name_entry))),
             ));
+        }
 
-            parent
-                .spawn((
-                    ButtonBundle {
-                        button: Button {},
-                        background_color: BackgroundColor::from(Color::BISQUE),
-                        ..default()
-                    },
-                    RestartButton {},
-                ))
-                .with_children(|parent| {
-                    parent.spawn(TextBundle {
-                        style: Style { ..default() },
-                        text: Text {
-                            sections: vec![TextSection::new(
-                                "Play Again",
-                                TextStyle {
-                                    font: asset_server.load("fonts/OpenSans.ttf"),
-                                    font_size: 45.,
-                                    color: Color::BLACK,
-                                },
-                            )],
-                            alignment: TextAlignment::Center,
-                            ..default()
-                        },
-                        ..default()
-                    });
-                });
+        widgets::row(parent, |parent| {
+            for (label, button) in [
+                ("Play Again", GameOverButton::PlayAgain),
+                ("Watch Replay", GameOverButton::WatchReplay),
+                ("Leaderboard", GameOverButton::Leaderboard),
+                ("Main Menu", GameOverButton::MainMenu),
+            ] {
+                widgets::button(parent, &theme, label, button);
+            }
         });
+    });
 }
 
 fn despawn_game_over(mut commands: Commands, q: Query<Entity, With<GameOverMenu>>) {
@@ -79,24 +154,56 @@ fn despawn_game_over(mut commands: Commands, q: Query<Entity, With<GameOverMenu>
     }
 }
 
-fn play_again_button(
-    mut button_q: Query<
-        (&Interaction, &mut BackgroundColor),
-        (Changed<Interaction>, With<RestartButton>),
-    >,
+fn game_over_buttons(
+    mut pressed: EventReader<focus::ButtonPressed>,
+    button_q: Query<&GameOverButton>,
     mut state: ResMut<NextState<state::AppState>>,
+    mut back_target: ResMut<menu::BackTarget>,
+    mut replay_requests: EventWriter<replay::ReplayRequest>,
 ) {
-    for (interaction, mut color) in button_q.iter_mut() {
-        match *interaction {
-            Interaction::Clicked => {
-                state.set(state::AppState::Running);
+    for button in button_q.iter_many(pressed.iter().map(|p| p.entity)) {
+        match button {
+            GameOverButton::PlayAgain => {
+                // after watching a replay this goes back to playing for real
+                replay_requests.send(replay::ReplayRequest::Stop);
+                state.set(state::AppState::Loading);
             }
-            Interaction::Hovered => {
-                *color = BackgroundColor::from(Color::LIME_GREEN);
+            GameOverButton::WatchReplay => {
+                replay_requests.send(replay::ReplayRequest::Watch);
+                state.set(state::AppState::Loading);
             }
-            _ => {
-                *color = BackgroundColor::from(Color::WHITE);
+            GameOverButton::Leaderboard => {
+                back_target.0 = state::AppState::GameOver;
+                state.set(state::AppState::Leaderboard);
             }
+            GameOverButton::MainMenu => state.set(state::AppState::MainMenu),
         }
     }
 }
+
+// Enter submits the name being typed, so it can't press buttons at the same time
+fn lock_focus_while_typing(
+    name_entry: Res<highscore::NameEntry>,
+    mut focus: ResMut<focus::UiFocus>,
+) {
+    if focus.locked != name_entry.active {
+        focus.locked = name_entry.active;
+    }
+}
+
+fn update_name_entry(
+    name_entry: Res<highscore::NameEntry>,
+    mut text_q: Query<&mut UiText, With<NameEntryText>>,
+) {
+    if !name_entry.is_changed() {
+        return;
+    }
+
+    if let Ok(mut text) = text_q.get_single_mut() {
+        text.value = name_entry_label(&name_entry);
+    }
+}
+
+fn unlock_focus(mut focus: ResMut<focus::UiFocus>) {
+    focus.locked = false;
+}
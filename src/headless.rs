@@ -0,0 +1,164 @@
+use bevy::{prelude::*, time::TimeUpdateStrategy, utils::Instant};
+
+use std::time::Duration;
+
+use crate::achievement;
+use crate::enemy;
+use crate::events;
+use crate::game_over;
+use crate::input::{GameInput, InputPlugin, InputSource};
+use crate::level;
+use crate::menu;
+use crate::player;
+use crate::playfield::Playfield;
+use crate::rng;
+use crate::run::RunPlugin;
+use crate::savegame;
+use crate::score;
+use crate::sets::GameSet;
+use crate::state::AppState;
+use crate::stats;
+use crate::timestep;
+use crate::transition;
+use crate::types;
+use crate::visuals::{Visuals, VisualsPlugin};
+
+/// How much game time each simulated frame covers, exactly one tick
+pub const FRAME: Duration = timestep::TICK;
+
+/// The rules of a run without a window, renderer or real clock.
+/// Every frame is exactly `FRAME` long and input comes from whoever calls `step`.
+pub struct Simulation {
+    pub app: App,
+    // the simulated clock, which only moves when a frame is stepped
+    now: Instant,
+}
+
+impl Simulation {
+    pub fn new(playfield: Playfield) -> Self {
+        let mut app = App::new();
+
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin::default())
+            .add_plugin(TransformPlugin)
+            .add_plugin(HierarchyPlugin)
+            .add_asset::<Image>()
+            .add_asset::<TextureAtlas>()
+            .add_asset::<Mesh>()
+            .add_asset::<ColorMaterial>()
+            .add_state::<AppState>()
+            .add_plugin(timestep::TimestepPlugin)
+            .add_plugin(VisualsPlugin)
+            .insert_resource(playfield)
+            .insert_resource(InputSource::Scripted)
+            // nothing ever starts a transition, so this just lets everything through
+            .init_resource::<transition::ScreenTransition>()
+            .add_plugin(events::EventsPlugin)
+            .add_plugin(RunPlugin)
+            .add_plugin(InputPlugin)
+            .add_plugin(rng::RngPlugin)
+            .add_plugin(enemy::EnemyPlugin)
+            .add_plugin(player::PlayerPlugin)
+            .add_plugin(level::LevelPlugin)
+            .add_plugin(score::ScorePlugin)
+            .add_plugin(stats::StatsPlugin)
+            .add_plugin(savegame::SaveGamePlugin)
+            .init_resource::<achievement::RunProgress>()
+            .insert_resource(menu::BackTarget(AppState::MainMenu))
+            .add_system(
+                game_over::end_run_on_turtle_hit
+                    .in_set(GameSet::Scoring)
+                    .in_set(OnUpdate(AppState::Running)),
+            );
+
+        let now = app.world.resource::<Time>().startup();
+
+        Simulation { app, now }
+    }
+
+    /// Starts a fresh run and steps until it's underway
+    pub fn start_run(&mut self) {
+        self.app
+            .world
+            .resource_mut::<NextState<AppState>>()
+            .set(AppState::Loading);
+
+        for _ in 0..10 {
+            self.idle();
+            if self.state() == AppState::Running {
+                return;
+            }
+        }
+
+        panic!("the run never started, stuck in {:?}", self.state());
+    }
+
+    /// Picks the run `snapshot` was taken from back up, the way continuing a saved run does
+    pub fn resume_run(&mut self, snapshot: savegame::RunSnapshot) {
+        let world = &mut self.app.world;
+        world.resource_mut::<level::SelectedLevel>().0 = snapshot.level;
+        *world.resource_mut::<player::Character>() = snapshot.character;
+        world.resource_mut::<savegame::PendingResume>().0 = Some(snapshot);
+
+        self.start_run();
+    }
+
+    /// Runs one frame with `input`, which only lasts for that frame
+    pub fn step(&mut self, input: GameInput) {
+        // `TimeUpdateStrategy::ManualDuration` counts from the real clock, so the time is set
+        // outright instead
+        self.now += FRAME;
+        *self.app.world.resource_mut::<TimeUpdateStrategy>() =
+            TimeUpdateStrategy::ManualInstant(self.now);
+        *self.app.world.resource_mut::<GameInput>() = input;
+        self.app.update();
+        *self.app.world.resource_mut::<GameInput>() = GameInput::default();
+    }
+
+    /// Runs one frame without touching anything
+    pub fn idle(&mut self) {
+        self.step(GameInput::default());
+    }
+
+    pub fn idle_for(&mut self, frames: usize) {
+        for _ in 0..frames {
+            self.idle();
+        }
+    }
+
+    pub fn state(&self) -> AppState {
+        self.app.world.resource::<State<AppState>>().0.clone()
+    }
+
+    pub fn score(&self) -> u32 {
+        self.app.world.resource::<score::Score>().val
+    }
+
+    pub fn player_position(&mut self) -> Vec2 {
+        self.app
+            .world
+            .query_filtered::<&Transform, With<player::Player>>()
+            .single(&self.app.world)
+            .translation
+            .truncate()
+    }
+
+    /// Puts a fish in the water at `position`, swimming in `direction`
+    pub fn spawn_fish(
+        &mut self,
+        fish_type: enemy::FishType,
+        direction: types::Dir,
+        position: Vec2,
+    ) -> Entity {
+        let world = &mut self.app.world;
+
+        let bundle = enemy::fish_bundle(
+            world.resource::<Visuals>(),
+            fish_type,
+            direction,
+            position,
+            0,
+        );
+        world.spawn(bundle).id()
+    }
+}
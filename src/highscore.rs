@@ -0,0 +1,261 @@
+use bevy::prelude::*;
+
+use crate::input;
+use crate::level;
+use crate::player;
+use crate::rng;
+use crate::run::RunApp;
+use crate::score;
+use crate::state;
+use crate::stats;
+use crate::storage;
+use crate::transition;
+
+pub struct HighScorePlugin;
+
+impl Plugin for HighScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_run_resource::<NameEntry>()
+            .add_startup_system(load_high_scores)
+            .add_system(
+                offer_name_entry
+                    .after(stats::finish_run)
+                    .in_schedule(OnEnter(state::AppState::GameOver)),
+            )
+            .add_system(
+                type_name
+                    .run_if(transition::idle)
+                    .in_set(OnUpdate(state::AppState::GameOver)),
+            )
+            .add_system(save_unnamed_entry.in_schedule(OnExit(state::AppState::GameOver)));
+    }
+}
+
+const FILE_NAME: &str = "highscores.txt";
+const HEADER: &str = "gonefishin-highscores";
+const VERSION: u32 = 1;
+
+pub const MAX_ENTRIES: usize = 10;
+const MAX_NAME_LEN: usize = 12;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HighScoreEntry {
+    pub name: String,
+    pub score: u32,
+    /// Unix timestamp of when the run ended
+    pub date: u64,
+    /// The lake the run was played in
+    pub mode: String,
+    pub seed: u64,
+    pub character: String,
+}
+
+impl HighScoreEntry {
+    fn to_line(&self) -> String {
+        format!(
+            "{}\t{}\t{}\t{}\t{}\t{}",
+            self.score, self.date, self.seed, self.mode, self.character, self.name
+        )
+    }
+
+    fn from_line(line: &str) -> Option<HighScoreEntry> {
+        let mut fields = line.split('\t');
+
+        let entry = HighScoreEntry {
+            score: fields.next()?.parse().ok()?,
+            date: fields.next()?.parse().ok()?,
+            seed: fields.next()?.parse().ok()?,
+            mode: fields.next()?.to_string(),
+            character: fields.next()?.to_string(),
+            name: fields.next()?.to_string(),
+        };
+
+        if fields.next().is_some() {
+            return None;
+        }
+
+        Some(entry)
+    }
+}
+
+/// The best runs ever played on this machine, highest score first
+#[derive(Resource, Default)]
+pub struct HighScores {
+    pub entries: Vec<HighScoreEntry>,
+    file: storage::VersionedFile,
+}
+
+impl HighScores {
+    /// Reads the table from `contents`, skipping any lines that don't make sense.
+    /// Fails only when the file was written by a newer version of the game.
+    pub fn parse(contents: &str) -> Result<Vec<HighScoreEntry>, storage::Newer> {
+        let mut entries: Vec<HighScoreEntry> = storage::versioned_lines(contents, HEADER, VERSION)?
+            .filter_map(|l| {
+                let entry = HighScoreEntry::from_line(l);
+                if entry.is_none() {
+                    warn!("skipping unreadable high score line {:?}", l);
+                }
+                entry
+            })
+            .collect();
+
+        entries.sort_by_key(|e| std::cmp::Reverse(e.score));
+        entries.truncate(MAX_ENTRIES);
+
+        Ok(entries)
+    }
+
+    pub fn serialize(&self) -> String {
+        let mut contents = format!("{} {}\n", HEADER, VERSION);
+        for entry in self.entries.iter() {
+            contents.push_str(&entry.to_line());
+            contents.push('\n');
+        }
+
+        contents
+    }
+
+    pub fn best(&self) -> u32 {
+        self.entries.first().map(|e| e.score).unwrap_or(0)
+    }
+
+    pub fn qualifies(&self, score: u32) -> bool {
+        score > 0
+            && (self.entries.len() < MAX_ENTRIES
+                || self.entries.last().map(|e| score > e.score).unwrap_or(true))
+    }
+
+    /// Adds a run to the table and returns its place, starting from 0
+    pub fn insert(&mut self, entry: HighScoreEntry) -> usize {
+        let rank = self
+            .entries
+            .iter()
+            .position(|e| entry.score > e.score)
+            .unwrap_or(self.entries.len());
+
+        self.entries.insert(rank, entry);
+        self.entries.truncate(MAX_ENTRIES);
+
+        rank
+    }
+
+    pub fn save(&self) {
+        self.file.save(self.serialize());
+    }
+}
+
+fn load_high_scores(mut commands: Commands, mut best: ResMut<stats::BestScore>) {
+    let (file, entries) = storage::VersionedFile::load(FILE_NAME, HighScores::parse);
+
+    let table = HighScores {
+        entries: entries.unwrap_or_default(),
+        file,
+    };
+
+    best.val = table.best();
+    commands.insert_resource(table);
+}
+
+/// The name being typed in for a run that made the high score table
+#[derive(Resource, Default)]
+pub struct NameEntry {
+    pub active: bool,
+    pub name: String,
+    /// Place in the table once the entry has been saved
+    pub saved_rank: Option<usize>,
+    offered: bool,
+}
+
+/// Asks for a name when the run that just ended made the table
+pub fn offer_name_entry(
+    score: Res<score::Score>,
+    table: Res<HighScores>,
+    source: Res<input::InputSource>,
+    mut entry: ResMut<NameEntry>,
+) {
+    // only offered once, so coming back from the leaderboard doesn't ask for a name again
+    if entry.offered {
+        return;
+    }
+
+    entry.offered = true;
+    // replays can't put a run on the table a second time
+    entry.active = *source == input::InputSource::Keyboard && table.qualifies(score.val);
+}
+
+/// Typing a name in for the high score table, Enter saves it
+#[allow(clippy::too_many_arguments)]
+pub fn type_name(
+    mut keys: ResMut<Input<KeyCode>>,
+    mut chars: EventReader<ReceivedCharacter>,
+    mut entry: ResMut<NameEntry>,
+    mut table: ResMut<HighScores>,
+    score: Res<score::Score>,
+    selected: Res<level::SelectedLevel>,
+    character: Res<player::Character>,
+    rng: Res<rng::GameRng>,
+) {
+    if !entry.active {
+        chars.clear();
+        return;
+    }
+
+    for c in chars.iter() {
+        if (c.char.is_ascii_alphanumeric() || c.char == ' ') && entry.name.len() < MAX_NAME_LEN {
+            entry.name.push(c.char);
+        }
+    }
+
+    if keys.just_pressed(KeyCode::Back) {
+        entry.name.pop();
+    }
+
+    if keys.just_pressed(KeyCode::Return) {
+        save_entry(&mut entry, &mut table, &score, &selected, &character, &rng);
+
+        // used up here, so the focused button doesn't take the same press once typing is over
+        keys.reset(KeyCode::Return);
+    }
+}
+
+// leaving the game over screen without pressing Enter still keeps the run on the table
+fn save_unnamed_entry(
+    mut entry: ResMut<NameEntry>,
+    mut table: ResMut<HighScores>,
+    score: Res<score::Score>,
+    selected: Res<level::SelectedLevel>,
+    character: Res<player::Character>,
+    rng: Res<rng::GameRng>,
+) {
+    if !entry.active {
+        return;
+    }
+
+    save_entry(&mut entry, &mut table, &score, &selected, &character, &rng);
+}
+
+// puts the run that just ended on the table, as "Player" if no name was typed
+fn save_entry(
+    entry: &mut NameEntry,
+    table: &mut HighScores,
+    score: &score::Score,
+    selected: &level::SelectedLevel,
+    character: &player::Character,
+    rng: &rng::GameRng,
+) {
+    let name = entry.name.trim();
+    let name = if name.is_empty() { "Player" } else { name };
+
+    let rank = table.insert(HighScoreEntry {
+        name: name.to_string(),
+        score: score.val,
+        date: storage::now(),
+        mode: selected.level().name.to_string(),
+        seed: rng.seed(),
+        character: character.name().to_string(),
+    });
+    table.save();
+
+    entry.active = false;
+    entry.saved_rank = Some(rank);
+}
@@ -0,0 +1,110 @@
+use bevy::{input::InputSystem, prelude::*};
+
+use crate::sets::GameSet;
+use crate::state;
+
+pub struct InputPlugin;
+
+impl Plugin for InputPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameInput>()
+            .init_resource::<InputSource>()
+            .init_resource::<KeyboardInput>()
+            .add_system(
+                read_keyboard
+                    .in_base_set(CoreSet::PreUpdate)
+                    .after(InputSystem)
+                    .in_set(GameSet::Input)
+                    .run_if(resource_equals(InputSource::Keyboard))
+                    .run_if(in_state(state::AppState::Running)),
+            )
+            .add_system(
+                latch_keyboard
+                    .in_set(GameSet::Input)
+                    .run_if(resource_equals(InputSource::Keyboard))
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
+            // presses from the end of one stretch of play don't carry over into the next
+            .add_system(clear_keyboard.in_schedule(OnExit(state::AppState::Running)));
+    }
+}
+
+/// Where gameplay input comes from
+#[derive(Resource, Default, PartialEq, Eq, Debug)]
+pub enum InputSource {
+    #[default]
+    Keyboard,
+    /// A recorded run is being played back
+    Replay,
+    /// Set directly by whoever is driving the app, like the headless tests
+    Scripted,
+}
+
+const LEFT: u8 = 1;
+const RIGHT: u8 = 1 << 1;
+const DROP: u8 = 1 << 2;
+const COLLECT: u8 = 1 << 3;
+
+/// What the player is asking for this tick, the only input gameplay looks at
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct GameInput {
+    pub left: bool,
+    pub right: bool,
+    /// Drop a bomb, or set off the one in the water
+    pub drop: bool,
+    /// Pick up the fish underneath
+    pub collect: bool,
+}
+
+impl GameInput {
+    /// Packs the input into a single byte for replays
+    pub fn to_bits(self) -> u8 {
+        let mut bits = 0;
+        for (pressed, bit) in [
+            (self.left, LEFT),
+            (self.right, RIGHT),
+            (self.drop, DROP),
+            (self.collect, COLLECT),
+        ] {
+            if pressed {
+                bits |= bit;
+            }
+        }
+
+        bits
+    }
+
+    pub fn from_bits(bits: u8) -> Self {
+        GameInput {
+            left: bits & LEFT != 0,
+            right: bits & RIGHT != 0,
+            drop: bits & DROP != 0,
+            collect: bits & COLLECT != 0,
+        }
+    }
+}
+
+// the keyboard since the last tick, frames and ticks don't line up so presses are held
+// until a tick takes them
+#[derive(Resource, Default)]
+struct KeyboardInput(GameInput);
+
+fn read_keyboard(keys: Res<Input<KeyCode>>, mut buffered: ResMut<KeyboardInput>) {
+    let input = &mut buffered.0;
+    input.left = keys.pressed(KeyCode::Left);
+    input.right = keys.pressed(KeyCode::Right);
+    input.drop |= keys.just_pressed(KeyCode::Space);
+    input.collect |= keys.just_pressed(KeyCode::A);
+}
+
+fn latch_keyboard(mut buffered: ResMut<KeyboardInput>, mut input: ResMut<GameInput>) {
+    *input = buffered.0;
+
+    // a press only counts for one tick
+    buffered.0.drop = false;
+    buffered.0.collect = false;
+}
+
+fn clear_keyboard(mut buffered: ResMut<KeyboardInput>) {
+    buffered.0 = GameInput::default();
+}
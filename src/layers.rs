@@ -0,0 +1,79 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+// What every collider in the lake is, and what it's allowed to touch.
+// Only sensors report anything, nothing in the lake pushes anything else around.
+
+/// Fish, alive or floating up
+pub const FISH: Group = Group::GROUP_1;
+/// The bomb sinking through the water
+pub const BOMB: Group = Group::GROUP_2;
+/// Explosions, which hit the fish they touch
+pub const EXPLOSION: Group = Group::GROUP_3;
+/// Just past the edges of the lake, where fish and bombs leave it
+pub const BLAST_ZONE: Group = Group::GROUP_4;
+/// The strip below the player that fish can be picked up from
+pub const REACH: Group = Group::GROUP_5;
+
+pub fn fish() -> CollisionGroups {
+    CollisionGroups::new(FISH, EXPLOSION | BLAST_ZONE | REACH)
+}
+
+pub fn bomb() -> CollisionGroups {
+    CollisionGroups::new(BOMB, BLAST_ZONE)
+}
+
+pub fn explosion() -> impl Bundle {
+    sensor(EXPLOSION, FISH)
+}
+
+pub fn blast_zone() -> impl Bundle {
+    sensor(BLAST_ZONE, FISH | BOMB)
+}
+
+pub fn reach() -> impl Bundle {
+    sensor(REACH, FISH)
+}
+
+// None of the colliders in the lake are on a moving body, so sensors have to be told to look
+// for overlaps between fixed ones too
+fn sensor(memberships: Group, filters: Group) -> impl Bundle {
+    (
+        Sensor,
+        CollisionGroups::new(memberships, filters),
+        ActiveEvents::COLLISION_EVENTS,
+        ActiveCollisionTypes::default() | ActiveCollisionTypes::STATIC_STATIC,
+    )
+}
+
+/// The two colliders that started overlapping in `event`, the one `is_ours` picks out first
+pub fn started(
+    event: &CollisionEvent,
+    is_ours: impl Fn(Entity) -> bool,
+) -> Option<(Entity, Entity)> {
+    match *event {
+        CollisionEvent::Started(a, b, _) => ours_first(a, b, is_ours),
+        CollisionEvent::Stopped(..) => None,
+    }
+}
+
+/// The two colliders that stopped overlapping in `event`, the one `is_ours` picks out first
+pub fn stopped(
+    event: &CollisionEvent,
+    is_ours: impl Fn(Entity) -> bool,
+) -> Option<(Entity, Entity)> {
+    match *event {
+        CollisionEvent::Stopped(a, b, _) => ours_first(a, b, is_ours),
+        CollisionEvent::Started(..) => None,
+    }
+}
+
+fn ours_first(a: Entity, b: Entity, is_ours: impl Fn(Entity) -> bool) -> Option<(Entity, Entity)> {
+    if is_ours(a) {
+        Some((a, b))
+    } else if is_ours(b) {
+        Some((b, a))
+    } else {
+        None
+    }
+}
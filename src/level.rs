@@ -1,121 +1,197 @@
 use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
 use bevy_rapier2d::prelude::*;
 
+use crate::current::{CurrentKind, CurrentZone};
+use crate::enemy;
+use crate::events;
+use crate::layers;
+use crate::player;
+use crate::playfield::Playfield;
+use crate::run::{RunScoped, RunSet};
+use crate::sets::GameSet;
 use crate::state;
 
 pub struct LevelPlugin;
 
 impl Plugin for LevelPlugin {
     fn build(&self, app: &mut App) {
-        app.add_startup_system(add_ground)
-            .add_startup_system(add_blast_zone)
-            .add_system(blast_zone_collisions.in_set(OnUpdate(state::AppState::Running)));
+        app.init_resource::<SelectedLevel>()
+            .add_systems(
+                (add_ground, add_blast_zone, start_run)
+                    .in_set(RunSet::Setup)
+                    .in_schedule(OnEnter(state::AppState::Loading)),
+            )
+            .add_system(
+                blast_zone_collisions
+                    .in_set(GameSet::Combat)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            );
+    }
+}
+
+/// Everything that makes one lake different from another.
+/// Positions are in world space, laid out for the default 1280x720 `Playfield`.
+pub struct LevelDef {
+    pub name: &'static str,
+    pub currents: &'static [CurrentZone],
+    /// Tile map of the shore, lake bed and rocks, see `tilemap::TileMap`
+    pub terrain: &'static str,
+}
+
+pub const LEVELS: &[LevelDef] = &[
+    LevelDef {
+        name: "Lazy River",
+        currents: &[
+            CurrentZone {
+                center: Vec2::new(0., -40.),
+                half_size: Vec2::new(700., 60.),
+                kind: CurrentKind::Flow(Vec2::new(40., 0.)),
+            },
+            CurrentZone {
+                center: Vec2::new(320., -100.),
+                half_size: Vec2::new(50., 260.),
+                kind: CurrentKind::Upwelling(60.),
+            },
+        ],
+        terrain: include_str!("../assets/levels/lazy_river.map"),
+    },
+    LevelDef {
+        name: "Whirlpool Cove",
+        currents: &[
+            CurrentZone {
+                center: Vec2::new(-220., -120.),
+                half_size: Vec2::new(130., 130.),
+                kind: CurrentKind::Whirlpool {
+                    spin: 90.,
+                    pull: 20.,
+                },
+            },
+            CurrentZone {
+                center: Vec2::new(0., -300.),
+                half_size: Vec2::new(700., 50.),
+                kind: CurrentKind::Flow(Vec2::new(-50., 0.)),
+            },
+        ],
+        terrain: include_str!("../assets/levels/whirlpool_cove.map"),
+    },
+    LevelDef {
+        name: "Still Waters",
+        currents: &[],
+        terrain: include_str!("../assets/levels/still_waters.map"),
+    },
+];
+
+/// The index into `LEVELS` of the lake being played
+#[derive(Resource, Default)]
+pub struct SelectedLevel(pub usize);
+
+impl SelectedLevel {
+    pub fn level(&self) -> &'static LevelDef {
+        &LEVELS[self.0]
     }
 }
 
 #[derive(Component)]
 pub struct Ground {}
 
+#[derive(Component)]
+struct Water {}
+
 fn add_ground(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
-    query: Query<&Window>,
+    playfield: Res<Playfield>,
 ) {
-    let window = query.single();
+    let top = playfield.water_line();
 
-    let top = (window.height() / 2.) - 150.;
-
-    // spawn land
+    // the shore itself is drawn by the tile map, this just marks the water line
     commands.spawn((
         Ground {},
+        RunScoped {},
+        TransformBundle::from(Transform::from_xyz(0., top, 0.1)),
+    ));
+
+    // spawn water
+    let water_depth = playfield.height;
+
+    commands.spawn((
+        Water {},
+        RunScoped {},
         MaterialMesh2dBundle {
             mesh: meshes
-                .add(shape::Quad::new(Vec2::new(window.width(), 30.)).into())
+                .add(shape::Quad::new(Vec2::new(playfield.width, water_depth)).into())
                 .into(),
-            material: materials.add(ColorMaterial::from(Color::hex("c4a484").unwrap())),
-            transform: Transform::from_xyz(0., top, 0.1),
+            material: materials.add(ColorMaterial::from(Color::hex("2063a5").unwrap())),
+            transform: Transform::from_xyz(0., top - (water_depth / 2.), 0.),
             ..default()
         },
     ));
+}
 
-    // spawn water
-    let water_depth = window.height();
-
-    commands.spawn(MaterialMesh2dBundle {
-        mesh: meshes
-            .add(shape::Quad::new(Vec2::new(window.width(), water_depth)).into())
-            .into(),
-        material: materials.add(ColorMaterial::from(Color::hex("2063a5").unwrap())),
-        transform: Transform::from_xyz(0., top - (water_depth / 2.), 0.),
-        ..default()
-    });
+// everything is in place once Loading has been entered, so the run can begin
+fn start_run(mut next_state: ResMut<NextState<state::AppState>>) {
+    next_state.set(state::AppState::Running);
 }
 
 #[derive(Component)]
 struct BlastZone {}
 
-fn add_blast_zone(mut commands: Commands, query: Query<&Window>) {
-    let window = query.single();
-
-    let window_h = window.height();
-    let window_w = window.width();
-
-    let bottom = 0. - (window_h / 2.) - 400.;
-    let left = 0. - (window_w / 2.) - 150.;
-    let right = (window_w / 2.) + 150.;
+fn add_blast_zone(mut commands: Commands, playfield: Res<Playfield>) {
+    let bottom = 0. - (playfield.height / 2.) - 400.;
+    let left = 0. - (playfield.width / 2.) - 150.;
+    let right = (playfield.width / 2.) + 150.;
 
     commands
         .spawn(RigidBody::Fixed)
-        .insert(Collider::cuboid(window_w, 10.))
-        .insert(ActiveCollisionTypes::default() | ActiveCollisionTypes::KINEMATIC_STATIC)
-        .insert(ActiveEvents::COLLISION_EVENTS)
-        .insert(CollisionGroups::new(
-            Group::from_bits(0b0001).unwrap(),
-            Group::from_bits(0b0010).unwrap(),
-        ))
+        .insert(Collider::cuboid(playfield.width, 10.))
+        .insert(layers::blast_zone())
         .insert(TransformBundle::from(Transform::from_xyz(0., bottom, 1.)))
-        .insert(BlastZone {});
+        .insert(BlastZone {})
+        .insert(RunScoped {});
 
     commands
         .spawn(RigidBody::Fixed)
-        .insert(Collider::cuboid(10., window_h))
-        .insert(ActiveCollisionTypes::default() | ActiveCollisionTypes::KINEMATIC_STATIC)
-        .insert(ActiveEvents::COLLISION_EVENTS)
-        .insert(CollisionGroups::new(
-            Group::from_bits(0b0001).unwrap(),
-            Group::from_bits(0b0010).unwrap(),
-        ))
+        .insert(Collider::cuboid(10., playfield.height))
+        .insert(layers::blast_zone())
         .insert(TransformBundle::from(Transform::from_xyz(right, 0., 1.)))
-        .insert(BlastZone {});
+        .insert(BlastZone {})
+        .insert(RunScoped {});
 
     commands
         .spawn(RigidBody::Fixed)
-        .insert(Collider::cuboid(10., window_h))
-        .insert(ActiveCollisionTypes::default() | ActiveCollisionTypes::KINEMATIC_STATIC)
-        .insert(ActiveEvents::COLLISION_EVENTS)
-        .insert(CollisionGroups::new(
-            Group::from_bits(0b0001).unwrap(),
-            Group::from_bits(0b0010).unwrap(),
-        ))
+        .insert(Collider::cuboid(10., playfield.height))
+        .insert(layers::blast_zone())
         .insert(TransformBundle::from(Transform::from_xyz(left, 0., 1.)))
-        .insert(BlastZone {});
+        .insert(BlastZone {})
+        .insert(RunScoped {});
 }
 
+#[allow(clippy::too_many_arguments)]
 fn blast_zone_collisions(
     mut commands: Commands,
-    rap_ctx: Res<RapierContext>,
-    mut query: Query<Entity, With<BlastZone>>,
+    mut fish_pool: ResMut<enemy::FishPool>,
+    mut bomb_pool: ResMut<player::BombPool>,
+    mut collisions: EventReader<CollisionEvent>,
+    zone_q: Query<(), With<BlastZone>>,
+    fish_q: Query<&enemy::Fish>,
+    bomb_q: Query<(), With<player::Bomb>>,
+    mut escaped: EventWriter<events::FishEscaped>,
 ) {
-    for bz in query.iter_mut() {
-        for contact_pair in rap_ctx.contacts_with(bz) {
-            let other_coll = if contact_pair.collider1() == bz {
-                contact_pair.collider2()
-            } else {
-                contact_pair.collider1()
-            };
-
-            commands.entity(other_coll).despawn();
+    for event in collisions.iter() {
+        let Some((_, other)) = layers::started(event, |e| zone_q.contains(e)) else {
+            continue;
+        };
+
+        if let Ok(fish) = fish_q.get(other) {
+            if fish.is_alive() {
+                escaped.send(events::FishEscaped {
+                    fish_type: fish.fish_type,
+                });
+            }
+            fish_pool.release(&mut commands, other);
+        } else if bomb_q.contains(other) {
+            bomb_pool.release(&mut commands, other);
         }
     }
 }
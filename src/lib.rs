@@ -0,0 +1,37 @@
+//! Gone Fishin' as a library, so the game rules can also be run without a window
+
+pub mod achievement;
+pub mod current;
+#[cfg(feature = "dev")]
+pub mod dev;
+pub mod enemy;
+pub mod events;
+pub mod feedback;
+pub mod game_over;
+pub mod headless;
+pub mod highscore;
+pub mod input;
+pub mod layers;
+pub mod level;
+pub mod menu;
+pub mod particles;
+pub mod pause;
+pub mod player;
+pub mod playfield;
+pub mod pool;
+pub mod replay;
+pub mod rng;
+pub mod run;
+pub mod savegame;
+pub mod score;
+pub mod sets;
+pub mod sound;
+pub mod state;
+pub mod stats;
+pub mod storage;
+pub mod tilemap;
+pub mod timestep;
+pub mod transition;
+pub mod types;
+pub mod ui;
+pub mod visuals;
@@ -1 +1,516 @@
-use bevy::prelude::*;
+use bevy::{app::AppExit, prelude::*};
+
+use crate::achievement;
+use crate::current;
+use crate::highscore;
+use crate::level;
+use crate::player;
+use crate::replay;
+use crate::savegame;
+use crate::sound;
+use crate::state;
+use crate::storage;
+use crate::ui::{
+    focus,
+    theme::{TextRole, Theme, UiText},
+    widgets,
+};
+
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(BackTarget(state::AppState::MainMenu))
+            .add_system(spawn_main_menu.in_schedule(OnEnter(state::AppState::MainMenu)))
+            .add_system(
+                main_menu_buttons
+                    .after(focus::FocusSet)
+                    .in_set(OnUpdate(state::AppState::MainMenu)),
+            )
+            .add_system(spawn_level_select.in_schedule(OnEnter(state::AppState::LevelSelect)))
+            .add_system(
+                level_buttons
+                    .after(focus::FocusSet)
+                    .in_set(OnUpdate(state::AppState::LevelSelect)),
+            )
+            .add_system(spawn_settings.in_schedule(OnEnter(state::AppState::Settings)))
+            .add_system(
+                settings_buttons
+                    .after(focus::FocusSet)
+                    .in_set(OnUpdate(state::AppState::Settings)),
+            )
+            .add_system(spawn_achievements.in_schedule(OnEnter(state::AppState::Achievements)))
+            .add_system(spawn_leaderboard.in_schedule(OnEnter(state::AppState::Leaderboard)))
+            .add_system(back_button.after(focus::FocusSet))
+            .add_systems((
+                despawn_menu.in_schedule(OnExit(state::AppState::MainMenu)),
+                despawn_menu.in_schedule(OnExit(state::AppState::LevelSelect)),
+                despawn_menu.in_schedule(OnExit(state::AppState::Settings)),
+                despawn_menu.in_schedule(OnExit(state::AppState::Achievements)),
+                despawn_menu.in_schedule(OnExit(state::AppState::Leaderboard)),
+            ));
+    }
+}
+
+// root node of whichever menu screen is showing
+#[derive(Component)]
+struct MenuScreen {}
+
+#[derive(Component)]
+enum MainMenuButton {
+    Continue,
+    Play,
+    WatchReplay,
+    LevelSelect,
+    Settings,
+    Achievements,
+    Leaderboard,
+    Quit,
+}
+
+#[derive(Component)]
+struct LevelButton {
+    level: usize,
+}
+
+#[derive(Component)]
+enum SettingsButton {
+    ShowCurrents,
+    Character,
+    Theme,
+    Font,
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+}
+
+// returns from any of the screens the main menu opens
+#[derive(Component)]
+struct BackButton {}
+
+/// Where the back button on the current menu screen goes.
+/// Screens opened from somewhere other than the main menu set this before switching.
+#[derive(Resource)]
+pub struct BackTarget(pub state::AppState);
+
+fn spawn_screen(commands: &mut Commands, theme: &Theme, title: &str) -> Entity {
+    let screen = widgets::screen(commands, theme, MenuScreen {});
+    commands.entity(screen).with_children(|parent| {
+        widgets::title(parent, title);
+    });
+
+    screen
+}
+
+fn spawn_button(
+    parent: &mut ChildBuilder,
+    asset_server: &AssetServer,
+    theme: &Theme,
+    icon: &str,
+    label: &str,
+    marker: impl Bundle,
+) {
+    widgets::icon_button(parent, theme, asset_server.load(icon), label, marker);
+}
+
+fn spawn_back_button(parent: &mut ChildBuilder, asset_server: &AssetServer, theme: &Theme) {
+    spawn_button(
+        parent,
+        asset_server,
+        theme,
+        "Free/Menu/Buttons/Back.png",
+        "Back",
+        BackButton {},
+    );
+}
+
+fn spawn_main_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    theme: Res<Theme>,
+    last_replay: Res<replay::LastReplay>,
+    saved_run: Res<savegame::SavedRun>,
+) {
+    let screen = spawn_screen(&mut commands, &theme, "Gone Fishin'");
+
+    commands.entity(screen).with_children(|parent| {
+        if saved_run.snapshot.is_some() {
+            spawn_button(
+                parent,
+                &asset_server,
+                &theme,
+                "Free/Menu/Buttons/Next.png",
+                "Continue",
+                MainMenuButton::Continue,
+            );
+        }
+
+        spawn_button(
+            parent,
+            &asset_server,
+            &theme,
+            "Free/Menu/Buttons/Play.png",
+            "Play",
+            MainMenuButton::Play,
+        );
+
+        if last_replay.recording.is_some() {
+            spawn_button(
+                parent,
+                &asset_server,
+                &theme,
+                "Free/Menu/Buttons/Restart.png",
+                "Watch Last Run",
+                MainMenuButton::WatchReplay,
+            );
+        }
+
+        let buttons = [
+            (
+                "Free/Menu/Buttons/Levels.png",
+                "Level Select",
+                MainMenuButton::LevelSelect,
+            ),
+            (
+                "Free/Menu/Buttons/Settings.png",
+                "Settings",
+                MainMenuButton::Settings,
+            ),
+            (
+                "Free/Menu/Buttons/Achievements.png",
+                "Achievements",
+                MainMenuButton::Achievements,
+            ),
+            (
+                "Free/Menu/Buttons/Leaderboard.png",
+                "Leaderboard",
+                MainMenuButton::Leaderboard,
+            ),
+            ("Free/Menu/Buttons/Close.png", "Quit", MainMenuButton::Quit),
+        ];
+
+        for (icon, label, button) in buttons {
+            spawn_button(parent, &asset_server, &theme, icon, label, button);
+        }
+    });
+}
+
+fn main_menu_buttons(
+    mut pressed: EventReader<focus::ButtonPressed>,
+    button_q: Query<&MainMenuButton>,
+    mut state: ResMut<NextState<state::AppState>>,
+    mut exit: EventWriter<AppExit>,
+    mut replay_requests: EventWriter<replay::ReplayRequest>,
+    mut continue_run: EventWriter<savegame::ContinueRun>,
+) {
+    for button in button_q.iter_many(pressed.iter().map(|p| p.entity)) {
+        match button {
+            // the saved run decides which lake it's in before loading
+            MainMenuButton::Continue => continue_run.send(savegame::ContinueRun),
+            MainMenuButton::Play => state.set(state::AppState::Loading),
+            MainMenuButton::WatchReplay => {
+                replay_requests.send(replay::ReplayRequest::Watch);
+                state.set(state::AppState::Loading);
+            }
+            MainMenuButton::LevelSelect => state.set(state::AppState::LevelSelect),
+            MainMenuButton::Settings => state.set(state::AppState::Settings),
+            MainMenuButton::Achievements => state.set(state::AppState::Achievements),
+            MainMenuButton::Leaderboard => state.set(state::AppState::Leaderboard),
+            MainMenuButton::Quit => exit.send(AppExit),
+        }
+    }
+}
+
+fn spawn_level_select(mut commands: Commands, asset_server: Res<AssetServer>, theme: Res<Theme>) {
+    let screen = spawn_screen(&mut commands, &theme, "Level Select");
+
+    commands.entity(screen).with_children(|parent| {
+        for (i, level) in level::LEVELS.iter().enumerate() {
+            let icon = format!("Free/Menu/Levels/{:02}.png", i + 1);
+            spawn_button(
+                parent,
+                &asset_server,
+                &theme,
+                &icon,
+                level.name,
+                LevelButton { level: i },
+            );
+        }
+
+        spawn_back_button(parent, &asset_server, &theme);
+    });
+}
+
+fn level_buttons(
+    mut pressed: EventReader<focus::ButtonPressed>,
+    button_q: Query<&LevelButton>,
+    mut selected: ResMut<level::SelectedLevel>,
+    mut state: ResMut<NextState<state::AppState>>,
+) {
+    for button in button_q.iter_many(pressed.iter().map(|p| p.entity)) {
+        selected.0 = button.level;
+        state.set(state::AppState::Loading);
+    }
+}
+
+fn show_currents_label(visualization: &current::CurrentVisualization) -> String {
+    if visualization.enabled {
+        "Currents: Shown".to_string()
+    } else {
+        "Currents: Hidden".to_string()
+    }
+}
+
+fn character_label(character: &player::Character) -> String {
+    format!("Character: {}", character.name())
+}
+
+fn theme_label(theme: &Theme) -> String {
+    format!("Theme: {}", theme.kind.name())
+}
+
+fn font_label(theme: &Theme) -> String {
+    format!("Font: {}", theme.face.name())
+}
+
+fn volume_label(name: &str, volume: f32) -> String {
+    format!("{}: {}%", name, (volume * 100.).round())
+}
+
+fn spawn_settings(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    theme: Res<Theme>,
+    visualization: Res<current::CurrentVisualization>,
+    character: Res<player::Character>,
+    volume: Res<sound::VolumeSettings>,
+) {
+    let screen = spawn_screen(&mut commands, &theme, "Settings");
+
+    commands.entity(screen).with_children(|parent| {
+        spawn_button(
+            parent,
+            &asset_server,
+            &theme,
+            "Free/Menu/Buttons/Settings.png",
+            &show_currents_label(&visualization),
+            SettingsButton::ShowCurrents,
+        );
+
+        spawn_button(
+            parent,
+            &asset_server,
+            &theme,
+            "Free/Menu/Buttons/Next.png",
+            &character_label(&character),
+            SettingsButton::Character,
+        );
+
+        spawn_button(
+            parent,
+            &asset_server,
+            &theme,
+            "Free/Menu/Buttons/Settings.png",
+            &theme_label(&theme),
+            SettingsButton::Theme,
+        );
+
+        spawn_button(
+            parent,
+            &asset_server,
+            &theme,
+            "Free/Menu/Buttons/Next.png",
+            &font_label(&theme),
+            SettingsButton::Font,
+        );
+
+        widgets::row(parent, |parent| {
+            widgets::button(
+                parent,
+                &theme,
+                &volume_label("Master", volume.master),
+                SettingsButton::MasterVolume,
+            );
+            widgets::button(
+                parent,
+                &theme,
+                &volume_label("Music", volume.music),
+                SettingsButton::MusicVolume,
+            );
+            widgets::button(
+                parent,
+                &theme,
+                &volume_label("Effects", volume.sfx),
+                SettingsButton::SfxVolume,
+            );
+        });
+
+        spawn_back_button(parent, &asset_server, &theme);
+    });
+}
+
+fn settings_buttons(
+    mut pressed: EventReader<focus::ButtonPressed>,
+    button_q: Query<(&SettingsButton, &Children)>,
+    mut text_q: Query<&mut UiText>,
+    mut visualization: ResMut<current::CurrentVisualization>,
+    mut character: ResMut<player::Character>,
+    mut theme: ResMut<Theme>,
+    mut volume: ResMut<sound::VolumeSettings>,
+) {
+    for (button, children) in button_q.iter_many(pressed.iter().map(|p| p.entity)) {
+        let label = match button {
+            SettingsButton::ShowCurrents => {
+                visualization.enabled = !visualization.enabled;
+                show_currents_label(&visualization)
+            }
+            SettingsButton::Character => {
+                *character = character.next();
+                character_label(&character)
+            }
+            SettingsButton::Theme => {
+                *theme = Theme::new(theme.kind.next(), theme.face, theme.font.clone());
+                theme_label(&theme)
+            }
+            SettingsButton::Font => {
+                theme.face = theme.face.next();
+                font_label(&theme)
+            }
+            SettingsButton::MasterVolume => {
+                volume.master = sound::step_volume(volume.master);
+                volume.save();
+                volume_label("Master", volume.master)
+            }
+            SettingsButton::MusicVolume => {
+                volume.music = sound::step_volume(volume.music);
+                volume.save();
+                volume_label("Music", volume.music)
+            }
+            SettingsButton::SfxVolume => {
+                volume.sfx = sound::step_volume(volume.sfx);
+                volume.save();
+                volume_label("Effects", volume.sfx)
+            }
+        };
+
+        widgets::set_button_label(children, &mut text_q, &label);
+    }
+}
+
+fn spawn_empty_screen(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    theme: &Theme,
+    title: &str,
+) {
+    let screen = spawn_screen(commands, theme, title);
+
+    commands.entity(screen).with_children(|parent| {
+        widgets::text(parent, TextRole::Heading, "Nothing here yet");
+
+        spawn_back_button(parent, asset_server, theme);
+    });
+}
+
+fn achievement_status(
+    achievements: &achievement::Achievements,
+    def: &achievement::AchievementDef,
+) -> String {
+    if let Some(&date) = achievements.unlocked.get(def.id) {
+        return format!("Unlocked {}", storage::format_date(date));
+    }
+
+    match achievements.progress(def) {
+        Some((done, needed)) => format!("Locked - {}/{}", done, needed),
+        None => "Locked".to_string(),
+    }
+}
+
+fn spawn_achievements(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    theme: Res<Theme>,
+    achievements: Res<achievement::Achievements>,
+) {
+    let screen = spawn_screen(&mut commands, &theme, "Achievements");
+
+    commands.entity(screen).with_children(|parent| {
+        widgets::grid(parent, |parent| {
+            for def in achievement::ACHIEVEMENTS {
+                widgets::card(parent, &theme, |parent| {
+                    // unlocked ones stand out, locked ones stay plain
+                    let name = if achievements.is_unlocked(def) {
+                        UiText::highlighted(TextRole::Heading, def.name)
+                    } else {
+                        UiText::new(TextRole::Heading, def.name)
+                    };
+                    parent.spawn(widgets::text_bundle(name));
+
+                    widgets::text(parent, TextRole::Small, def.description);
+                    widgets::text(
+                        parent,
+                        TextRole::Small,
+                        achievement_status(&achievements, def),
+                    );
+                });
+            }
+        });
+
+        spawn_back_button(parent, &asset_server, &theme);
+    });
+}
+
+fn spawn_leaderboard(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    theme: Res<Theme>,
+    table: Res<highscore::HighScores>,
+) {
+    if table.entries.is_empty() {
+        spawn_empty_screen(&mut commands, &asset_server, &theme, "Leaderboard");
+        return;
+    }
+
+    let screen = spawn_screen(&mut commands, &theme, "Leaderboard");
+
+    commands.entity(screen).with_children(|parent| {
+        widgets::panel(parent, &theme, |parent| {
+            for (rank, entry) in table.entries.iter().enumerate() {
+                widgets::text(
+                    parent,
+                    TextRole::Small,
+                    format!(
+                        "{}. {} - {} - {} as {} on {} (seed {})",
+                        rank + 1,
+                        entry.name,
+                        entry.score,
+                        entry.mode,
+                        entry.character,
+                        storage::format_date(entry.date),
+                        entry.seed
+                    ),
+                );
+            }
+        });
+
+        spawn_back_button(parent, &asset_server, &theme);
+    });
+}
+
+fn back_button(
+    mut pressed: EventReader<focus::ButtonPressed>,
+    button_q: Query<&BackButton>,
+    mut state: ResMut<NextState<state::AppState>>,
+    mut back_target: ResMut<BackTarget>,
+) {
+    for _ in button_q.iter_many(pressed.iter().map(|p| p.entity)) {
+        state.set(back_target.0.clone());
+        back_target.0 = state::AppState::MainMenu;
+    }
+}
+
+fn despawn_menu(mut commands: Commands, q: Query<Entity, With<MenuScreen>>) {
+    for e in q.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+}
@@ -0,0 +1,469 @@
+use bevy::{prelude::*, render::texture::DEFAULT_IMAGE_HANDLE};
+use rand::Rng;
+
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use crate::achievement;
+use crate::enemy;
+use crate::events;
+use crate::level;
+use crate::player;
+use crate::playfield::Playfield;
+use crate::run::RunApp;
+use crate::sets::GameSet;
+
+/// Small sprites for explosions, splashes, bubbles and confetti.
+/// Every particle comes out of a pool made up front, so effects never spawn or despawn anything.
+pub struct ParticlePlugin;
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ParticleTextures>()
+            .init_resource::<ParticlePool>()
+            .add_event::<SpawnParticles>()
+            .add_startup_system(fill_pool)
+            .add_run_teardown(clear_particles)
+            .add_systems((gameplay_effects, attach_trails).in_set(GameSet::Ui))
+            // things only move on ticks, so that's when trails look at how far they've gone
+            .add_system(
+                emit_trails
+                    .after(GameSet::Movement)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
+            .add_system(emit_particles.after(gameplay_effects).in_set(GameSet::Ui))
+            .add_system(update_particles.after(emit_particles).in_set(GameSet::Ui));
+    }
+}
+
+// the most particles that can be on screen at once, anything past this is skipped
+const POOL_SIZE: usize = 600;
+// drawn above the fish and bombs but under the score popups
+const PARTICLE_Z: f32 = 4.;
+// trails only bubble while whatever they follow is sinking or rising at least this fast
+const TRAIL_MIN_SPEED: f32 = 5.;
+
+const CONFETTI_FRAMES: usize = 6;
+const CONFETTI_FRAME_SIZE: f32 = 16.;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ParticleTexture {
+    /// A plain square, tinted by the particle's color
+    Plain,
+    Dust,
+    /// One of the confetti pieces, picked at random
+    Confetti,
+}
+
+/// Everything about how an effect looks. Ranges are picked from at random for each particle.
+pub struct EmitterDef {
+    /// How many particles a single burst makes
+    pub count: u32,
+    /// Particles a second, for trails that follow something around
+    pub rate: f32,
+    /// Seconds each particle lasts
+    pub lifetime: (f32, f32),
+    pub speed: (f32, f32),
+    /// Which way particles head off in, in radians from pointing right
+    pub angle: f32,
+    /// How far either side of `angle` they can go
+    pub spread: f32,
+    /// Half the size of the box particles start somewhere in
+    pub area: Vec2,
+    /// Upwards acceleration, negative to fall
+    pub gravity: f32,
+    /// How much of their speed particles lose each second
+    pub drag: f32,
+    /// Size when a particle starts out and when it's about to vanish
+    pub size: (f32, f32),
+    /// Fastest a particle can spin, in radians a second
+    pub spin: f32,
+    /// Each particle is one of these colors, fading out over its life
+    pub colors: &'static [Color],
+    pub texture: ParticleTexture,
+}
+
+pub const EXPLOSION_DEBRIS: EmitterDef = EmitterDef {
+    count: 24,
+    rate: 0.,
+    lifetime: (0.4, 0.8),
+    speed: (120., 320.),
+    angle: FRAC_PI_2,
+    spread: PI,
+    area: Vec2::splat(6.),
+    gravity: -400.,
+    drag: 2.,
+    size: (6., 2.),
+    spin: 10.,
+    colors: &[Color::ORANGE_RED, Color::ORANGE, Color::YELLOW],
+    texture: ParticleTexture::Plain,
+};
+
+pub const EXPLOSION_SMOKE: EmitterDef = EmitterDef {
+    count: 10,
+    rate: 0.,
+    lifetime: (0.8, 1.4),
+    speed: (10., 50.),
+    angle: FRAC_PI_2,
+    spread: PI,
+    area: Vec2::splat(15.),
+    gravity: 30.,
+    drag: 1.,
+    size: (16., 40.),
+    spin: 1.,
+    colors: &[
+        Color::rgba(0.4, 0.4, 0.4, 0.7),
+        Color::rgba(0.55, 0.55, 0.55, 0.6),
+    ],
+    texture: ParticleTexture::Dust,
+};
+
+pub const SPLASH: EmitterDef = EmitterDef {
+    count: 16,
+    rate: 0.,
+    lifetime: (0.3, 0.6),
+    speed: (80., 220.),
+    angle: FRAC_PI_2,
+    spread: 0.6,
+    area: Vec2::new(8., 0.),
+    gravity: -600.,
+    drag: 0.,
+    size: (5., 3.),
+    spin: 0.,
+    colors: &[Color::WHITE, Color::rgb(0.7, 0.85, 1.)],
+    texture: ParticleTexture::Plain,
+};
+
+pub const BUBBLES: EmitterDef = EmitterDef {
+    count: 1,
+    rate: 12.,
+    lifetime: (0.6, 1.2),
+    speed: (20., 40.),
+    angle: FRAC_PI_2,
+    spread: 0.4,
+    area: Vec2::new(6., 4.),
+    gravity: 40.,
+    drag: 0.5,
+    size: (3., 7.),
+    spin: 0.,
+    colors: &[Color::rgba(0.8, 0.9, 1., 0.7)],
+    texture: ParticleTexture::Dust,
+};
+
+pub const CONFETTI: EmitterDef = EmitterDef {
+    count: 80,
+    rate: 0.,
+    lifetime: (1.5, 2.5),
+    speed: (20., 120.),
+    angle: -FRAC_PI_2,
+    spread: 0.8,
+    area: Vec2::new(600., 10.),
+    gravity: -120.,
+    drag: 0.8,
+    size: (16., 12.),
+    spin: 6.,
+    colors: &[Color::WHITE],
+    texture: ParticleTexture::Confetti,
+};
+
+/// Asks for a burst of particles at `position`
+pub struct SpawnParticles {
+    pub def: &'static EmitterDef,
+    pub position: Vec2,
+    pub count: u32,
+}
+
+impl SpawnParticles {
+    /// The emitter's usual burst
+    pub fn burst(def: &'static EmitterDef, position: Vec2) -> Self {
+        SpawnParticles {
+            def,
+            position,
+            count: def.count,
+        }
+    }
+}
+
+#[derive(Resource)]
+struct ParticleTextures {
+    dust: Handle<Image>,
+    confetti: Handle<Image>,
+}
+
+impl FromWorld for ParticleTextures {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+
+        ParticleTextures {
+            dust: asset_server.load("Free/Other/Dust Particle.png"),
+            confetti: asset_server.load("Free/Other/Confetti (16x16).png"),
+        }
+    }
+}
+
+// particles that are hidden and ready to be handed out again
+#[derive(Resource, Default)]
+struct ParticlePool {
+    free: Vec<Entity>,
+}
+
+#[derive(Component, Default)]
+struct Particle {
+    velocity: Vec2,
+    age: f32,
+    lifetime: f32,
+    gravity: f32,
+    drag: f32,
+    size: (f32, f32),
+    spin: f32,
+    color: Color,
+}
+
+/// Bubbles from behind whatever it's on while it sinks or rises through the water
+#[derive(Component)]
+pub struct Trail {
+    def: &'static EmitterDef,
+    last_position: Vec2,
+    // part of a particle carried over to the next tick, so low rates still come out right
+    pending: f32,
+}
+
+fn fill_pool(mut commands: Commands, mut pool: ResMut<ParticlePool>) {
+    pool.free = (0..POOL_SIZE)
+        .map(|_| {
+            commands
+                .spawn((
+                    Particle::default(),
+                    SpriteBundle {
+                        visibility: Visibility::Hidden,
+                        ..default()
+                    },
+                ))
+                .id()
+        })
+        .collect();
+}
+
+fn gameplay_effects(
+    playfield: Res<Playfield>,
+    mut detonated: EventReader<events::BombDetonated>,
+    mut splashed: EventReader<events::BombSplashed>,
+    mut cleared: EventReader<events::WaveCleared>,
+    mut unlocked: EventReader<achievement::AchievementUnlocked>,
+    mut spawn: EventWriter<SpawnParticles>,
+) {
+    for detonation in detonated.iter() {
+        spawn.send(SpawnParticles::burst(
+            &EXPLOSION_DEBRIS,
+            detonation.position,
+        ));
+        spawn.send(SpawnParticles::burst(&EXPLOSION_SMOKE, detonation.position));
+    }
+
+    for splash in splashed.iter() {
+        spawn.send(SpawnParticles::burst(&SPLASH, splash.position));
+    }
+
+    // confetti rains down from the top of the screen
+    let milestones = cleared.iter().count() + unlocked.iter().count();
+    if milestones > 0 {
+        spawn.send(SpawnParticles::burst(
+            &CONFETTI,
+            Vec2::new(0., playfield.height / 2.),
+        ));
+    }
+}
+
+// bombs bubble on the way down and dead fish on the way back up
+#[allow(clippy::type_complexity)]
+fn attach_trails(
+    mut commands: Commands,
+    bomb_q: Query<(Entity, &Transform), (With<player::Bomb>, Without<Trail>)>,
+    fish_q: Query<(Entity, &enemy::Fish, &Transform), Without<Trail>>,
+) {
+    let dead_fish = fish_q
+        .iter()
+        .filter(|(_, fish, _)| !fish.is_alive())
+        .map(|(entity, _, transform)| (entity, transform));
+
+    for (entity, transform) in bomb_q.iter().chain(dead_fish) {
+        commands.entity(entity).insert(Trail {
+            def: &BUBBLES,
+            last_position: transform.translation.truncate(),
+            pending: 0.,
+        });
+    }
+}
+
+fn emit_trails(
+    fixed_time: Res<FixedTime>,
+    ground_q: Query<&Transform, With<level::Ground>>,
+    mut trail_q: Query<(&Transform, &mut Trail)>,
+    mut spawn: EventWriter<SpawnParticles>,
+) {
+    let dt = fixed_time.period.as_secs_f32();
+
+    let water_line = ground_q
+        .get_single()
+        .map_or(f32::INFINITY, |ground| ground.translation.y);
+
+    for (transform, mut trail) in trail_q.iter_mut() {
+        let position = transform.translation.truncate();
+        // only up and down counts, drifting along in the current doesn't leave bubbles
+        let speed = (position.y - trail.last_position.y).abs() / dt;
+        trail.last_position = position;
+
+        if speed < TRAIL_MIN_SPEED || position.y >= water_line {
+            trail.pending = 0.;
+            continue;
+        }
+
+        trail.pending += trail.def.rate * dt;
+        let count = trail.pending.floor();
+        trail.pending -= count;
+
+        if count > 0. {
+            spawn.send(SpawnParticles {
+                def: trail.def,
+                position,
+                count: count as u32,
+            });
+        }
+    }
+}
+
+fn random_between(rng: &mut impl Rng, (low, high): (f32, f32)) -> f32 {
+    if low < high {
+        rng.gen_range(low..high)
+    } else {
+        low
+    }
+}
+
+fn emit_particles(
+    textures: Res<ParticleTextures>,
+    mut pool: ResMut<ParticlePool>,
+    mut requests: EventReader<SpawnParticles>,
+    mut particle_q: Query<(
+        &mut Particle,
+        &mut Transform,
+        &mut Sprite,
+        &mut Handle<Image>,
+        &mut Visibility,
+    )>,
+) {
+    // effects don't change how a run plays out, so they stay off the game's own generator
+    // and replays line up no matter how many particles there have been
+    let mut rng = rand::thread_rng();
+
+    for request in requests.iter() {
+        let def = request.def;
+
+        for _ in 0..request.count {
+            // every particle is already out, so this burst comes up short
+            let Some(entity) = pool.free.pop() else {
+                break;
+            };
+
+            let Ok((mut particle, mut transform, mut sprite, mut texture, mut visibility)) =
+                particle_q.get_mut(entity)
+            else {
+                continue;
+            };
+
+            let angle = def.angle + random_between(&mut rng, (-def.spread, def.spread));
+            let speed = random_between(&mut rng, def.speed);
+            let offset = Vec2::new(
+                random_between(&mut rng, (-def.area.x, def.area.x)),
+                random_between(&mut rng, (-def.area.y, def.area.y)),
+            );
+            let color = def.colors[rng.gen_range(0..def.colors.len())];
+
+            *particle = Particle {
+                velocity: Vec2::from_angle(angle) * speed,
+                age: 0.,
+                lifetime: random_between(&mut rng, def.lifetime),
+                gravity: def.gravity,
+                drag: def.drag,
+                size: def.size,
+                spin: random_between(&mut rng, (-def.spin, def.spin)),
+                color,
+            };
+
+            let position = request.position + offset;
+            *transform = Transform::from_xyz(position.x, position.y, PARTICLE_Z);
+
+            sprite.color = color;
+            sprite.custom_size = Some(Vec2::splat(def.size.0));
+            (*texture, sprite.rect) = match def.texture {
+                ParticleTexture::Plain => (DEFAULT_IMAGE_HANDLE.typed(), None),
+                ParticleTexture::Dust => (textures.dust.clone(), None),
+                ParticleTexture::Confetti => {
+                    let frame = rng.gen_range(0..CONFETTI_FRAMES) as f32;
+                    let min = Vec2::new(frame * CONFETTI_FRAME_SIZE, 0.);
+                    (
+                        textures.confetti.clone(),
+                        Some(Rect::from_corners(
+                            min,
+                            min + Vec2::splat(CONFETTI_FRAME_SIZE),
+                        )),
+                    )
+                }
+            };
+
+            *visibility = Visibility::Inherited;
+        }
+    }
+}
+
+fn update_particles(
+    time: Res<Time>,
+    mut pool: ResMut<ParticlePool>,
+    mut particle_q: Query<(
+        Entity,
+        &mut Particle,
+        &mut Transform,
+        &mut Sprite,
+        &mut Visibility,
+    )>,
+) {
+    let dt = time.delta_seconds();
+
+    for (entity, mut particle, mut transform, mut sprite, mut visibility) in particle_q.iter_mut() {
+        let particle = &mut *particle;
+
+        if *visibility == Visibility::Hidden {
+            continue;
+        }
+
+        particle.age += dt;
+        if particle.age >= particle.lifetime {
+            *visibility = Visibility::Hidden;
+            pool.free.push(entity);
+            continue;
+        }
+
+        particle.velocity.y += particle.gravity * dt;
+        particle.velocity *= (1. - particle.drag * dt).max(0.);
+
+        transform.translation += (particle.velocity * dt).extend(0.);
+        transform.rotate_z(particle.spin * dt);
+
+        let t = particle.age / particle.lifetime;
+        let (start, end) = particle.size;
+        sprite.custom_size = Some(Vec2::splat(start + (end - start) * t));
+        sprite.color = particle.color.with_a(particle.color.a() * (1. - t));
+    }
+}
+
+// hands every particle back to the pool, so nothing from the last run is left floating about
+fn clear_particles(
+    mut pool: ResMut<ParticlePool>,
+    mut particle_q: Query<(Entity, &mut Visibility), With<Particle>>,
+) {
+    for (entity, mut visibility) in particle_q.iter_mut() {
+        if *visibility != Visibility::Hidden {
+            *visibility = Visibility::Hidden;
+            pool.free.push(entity);
+        }
+    }
+}
@@ -1,57 +1,120 @@
 use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
 
+use crate::menu;
+use crate::savegame;
 use crate::state;
+use crate::transition;
+use crate::ui::{focus, theme::Theme, widgets};
 
 pub struct PausePlugin;
 
 impl Plugin for PausePlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(pause_game.in_set(OnUpdate(state::AppState::Running)))
-            .add_system(unpause_game.in_set(OnUpdate(state::AppState::Paused)))
-            .add_system(spawn_pause_menu.in_schedule(OnEnter(state::AppState::Paused)))
-            .add_system(despawn_pause_menu.in_schedule(OnExit(state::AppState::Paused)));
+        app.add_system(
+            pause_game
+                .run_if(transition::idle)
+                .in_set(OnUpdate(state::AppState::Running)),
+        )
+        .add_systems(
+            (
+                unpause_game.run_if(transition::idle),
+                pause_buttons.after(focus::FocusSet),
+            )
+                .in_set(OnUpdate(state::AppState::Paused)),
+        )
+        .add_systems((freeze_time, spawn_pause_menu).in_schedule(OnEnter(state::AppState::Paused)))
+        .add_systems(
+            (unfreeze_time, despawn_pause_menu).in_schedule(OnExit(state::AppState::Paused)),
+        )
+        .add_system(resume_physics.in_schedule(OnEnter(state::AppState::Running)))
+        .add_system(suspend_physics.in_schedule(OnExit(state::AppState::Running)));
     }
 }
 
-fn pause_game(keys: Res<Input<KeyCode>>, mut next_state: ResMut<NextState<state::AppState>>) {
-    if keys.just_pressed(KeyCode::Escape) {
+fn pause_game(
+    keys: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+    mut next_state: ResMut<NextState<state::AppState>>,
+) {
+    if keys.just_pressed(KeyCode::Escape)
+        || focus::gamepad_just_pressed(&gamepads, &buttons, &[GamepadButtonType::Start])
+    {
         next_state.set(state::AppState::Paused);
     }
 }
 
-fn unpause_game(keys: Res<Input<KeyCode>>, mut next_state: ResMut<NextState<state::AppState>>) {
-    if keys.just_pressed(KeyCode::Escape) {
+fn unpause_game(
+    keys: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+    mut next_state: ResMut<NextState<state::AppState>>,
+) {
+    if keys.just_pressed(KeyCode::Escape)
+        || focus::gamepad_just_pressed(&gamepads, &buttons, &[GamepadButtonType::Start])
+    {
         next_state.set(state::AppState::Running);
     }
 }
 
+// rapier keeps stepping on its own schedule, so it has to be told to stop
+// whenever the game isn't running
+fn resume_physics(mut rapier_config: ResMut<RapierConfiguration>) {
+    rapier_config.physics_pipeline_active = true;
+    rapier_config.query_pipeline_active = true;
+}
+
+fn suspend_physics(mut rapier_config: ResMut<RapierConfiguration>) {
+    rapier_config.physics_pipeline_active = false;
+    rapier_config.query_pipeline_active = false;
+}
+
+// stops the clock so no timer or delta based movement advances while paused
+fn freeze_time(mut time: ResMut<Time>) {
+    time.pause();
+}
+
+fn unfreeze_time(mut time: ResMut<Time>) {
+    time.unpause();
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum PauseAction {
+    Resume,
+    Restart,
+    Settings,
+    SaveAndQuit,
+    Quit,
+}
+
+// in the order they show up in the menu
+const PAUSE_ACTIONS: [(PauseAction, &str); 5] = [
+    (PauseAction::Resume, "Resume"),
+    (PauseAction::Restart, "Restart"),
+    (PauseAction::Settings, "Settings"),
+    (PauseAction::SaveAndQuit, "Save and Quit"),
+    (PauseAction::Quit, "Quit to Menu"),
+];
+
 #[derive(Component)]
 struct PauseMenu {}
 
-fn spawn_pause_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
-    commands
-        .spawn((
-            PauseMenu {},
-            NodeBundle {
-                style: Style {
-                    size: Size::width(Val::Percent(100.)),
-                    justify_content: JustifyContent::Center,
-                    align_items: AlignItems::Center,
-                    ..default()
-                },
-                ..default()
-            },
-        ))
-        .with_children(|parent| {
-            parent.spawn(TextBundle::from_section(
-                "Paused",
-                TextStyle {
-                    font: asset_server.load("fonts/OpenSans.ttf"),
-                    font_size: 45.,
-                    color: Color::BLACK,
-                },
-            ));
-        });
+#[derive(Component)]
+struct PauseButton {
+    index: usize,
+}
+
+fn spawn_pause_menu(mut commands: Commands, theme: Res<Theme>) {
+    let menu = widgets::overlay(&mut commands, &theme, PauseMenu {});
+
+    commands.entity(menu).with_children(|parent| {
+        widgets::title(parent, "Paused");
+
+        for (index, (_, label)) in PAUSE_ACTIONS.iter().enumerate() {
+            widgets::button(parent, &theme, label, PauseButton { index });
+        }
+    });
 }
 
 fn despawn_pause_menu(mut commands: Commands, q: Query<Entity, With<PauseMenu>>) {
@@ -59,3 +122,42 @@ fn despawn_pause_menu(mut commands: Commands, q: Query<Entity, With<PauseMenu>>)
         commands.entity(e).despawn_recursive();
     }
 }
+
+fn run_pause_action(
+    action: PauseAction,
+    next_state: &mut NextState<state::AppState>,
+    back_target: &mut menu::BackTarget,
+    save: &mut EventWriter<savegame::SaveRun>,
+) {
+    match action {
+        PauseAction::Resume => next_state.set(state::AppState::Running),
+        PauseAction::Restart => next_state.set(state::AppState::Loading),
+        PauseAction::Settings => {
+            // come back here instead of the main menu when leaving settings
+            back_target.0 = state::AppState::Paused;
+            next_state.set(state::AppState::Settings);
+        }
+        PauseAction::SaveAndQuit => {
+            save.send(savegame::SaveRun);
+            next_state.set(state::AppState::MainMenu);
+        }
+        PauseAction::Quit => next_state.set(state::AppState::MainMenu),
+    }
+}
+
+fn pause_buttons(
+    mut pressed: EventReader<focus::ButtonPressed>,
+    button_q: Query<&PauseButton>,
+    mut next_state: ResMut<NextState<state::AppState>>,
+    mut back_target: ResMut<menu::BackTarget>,
+    mut save: EventWriter<savegame::SaveRun>,
+) {
+    for button in button_q.iter_many(pressed.iter().map(|p| p.entity)) {
+        run_pause_action(
+            PAUSE_ACTIONS[button.index].0,
+            &mut next_state,
+            &mut back_target,
+            &mut save,
+        );
+    }
+}
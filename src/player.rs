@@ -3,30 +3,67 @@ use bevy_rapier2d::prelude::*;
 
 use std::time::Duration;
 
+use crate::current;
 use crate::enemy;
-use crate::score;
+use crate::events;
+use crate::input;
+use crate::layers;
+use crate::level;
+use crate::particles;
+use crate::playfield::Playfield;
+use crate::pool::Pool;
+use crate::run::{self, RunApp, RunSet};
+use crate::sets::GameSet;
 use crate::state;
+use crate::tilemap;
+use crate::timestep;
+use crate::transition;
 use crate::types;
+use crate::visuals::Visuals;
 
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_startup_system(player_setup)
+        app.init_resource::<Character>()
+            .init_resource::<PlayerTuning>()
+            .init_run_pool::<BombPool>()
+            .init_run_pool::<ExplosionPool>()
+            .add_system(
+                player_setup
+                    .in_set(RunSet::Setup)
+                    .in_schedule(OnEnter(state::AppState::Loading)),
+            )
+            .add_system(
+                animate_sprites
+                    .in_set(GameSet::Ui)
+                    .in_set(OnUpdate(state::AppState::Running)),
+            )
             .add_systems(
                 (
-                    animate_sprites,
-                    player_movement,
-                    flip_player,
-                    bomb_drop,
-                    bomb_movement,
-                    clear_explosion,
-                    check_for_fish,
+                    player_movement.run_if(transition::idle),
+                    flip_player.run_if(transition::idle),
                 )
-                    .in_set(OnUpdate(state::AppState::Running)),
+                    .chain()
+                    .in_set(GameSet::Movement)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
+            .add_system(
+                bomb_movement
+                    .before(current::apply_currents)
+                    .in_set(GameSet::Movement)
+                    .in_schedule(CoreSchedule::FixedUpdate),
             )
             .add_systems(
-                (reset_player, clear_explosions).in_schedule(OnEnter(state::AppState::GameOver)),
+                (
+                    bomb_drop.run_if(transition::idle),
+                    clear_explosion,
+                    track_reach,
+                    check_for_fish.run_if(transition::idle),
+                )
+                    .chain()
+                    .in_set(GameSet::Combat)
+                    .in_schedule(CoreSchedule::FixedUpdate),
             );
     }
 }
@@ -34,9 +71,79 @@ impl Plugin for PlayerPlugin {
 #[derive(Component)]
 pub struct Player;
 
+/// How fast the player walks and their bombs sink, kept apart so they can be tuned while playing
+#[derive(Resource)]
+pub struct PlayerTuning {
+    pub walk_speed: f32,
+    pub bomb_sink_speed: f32,
+}
+
+impl Default for PlayerTuning {
+    fn default() -> Self {
+        PlayerTuning {
+            walk_speed: 150.,
+            bomb_sink_speed: 150.,
+        }
+    }
+}
+
+/// Which of the bundled characters the player fishes as
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Character {
+    #[default]
+    NinjaFrog,
+    MaskDude,
+    PinkMan,
+    VirtualGuy,
+}
+
+impl Character {
+    pub const ALL: [Character; 4] = [
+        Character::NinjaFrog,
+        Character::MaskDude,
+        Character::PinkMan,
+        Character::VirtualGuy,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Character::NinjaFrog => "Ninja Frog",
+            Character::MaskDude => "Mask Dude",
+            Character::PinkMan => "Pink Man",
+            Character::VirtualGuy => "Virtual Guy",
+        }
+    }
+
+    /// The character after this one, wrapping back around to the first
+    pub fn next(&self) -> Character {
+        match self {
+            Character::NinjaFrog => Character::MaskDude,
+            Character::MaskDude => Character::PinkMan,
+            Character::PinkMan => Character::VirtualGuy,
+            Character::VirtualGuy => Character::NinjaFrog,
+        }
+    }
+
+    fn idle_sheet(&self) -> String {
+        format!("Free/Main Characters/{}/Idle (32x32).png", self.name())
+    }
+}
+
+/// Which way the player is facing
 #[derive(Component)]
-struct Direction {
-    dir: types::Dir,
+pub struct Direction {
+    pub dir: types::Dir,
+}
+
+impl Direction {
+    /// Faces the player the other way, flipping the sprite to match
+    pub fn turn(&mut self, transform: &mut Transform, dir: types::Dir) {
+        transform.rotation = match dir {
+            types::Dir::Backward => Quat::from_rotation_y(std::f32::consts::PI),
+            _ => Quat::default(),
+        };
+        self.dir = dir;
+    }
 }
 
 #[derive(Component)]
@@ -48,49 +155,48 @@ struct AnimationIndices {
 #[derive(Component, Deref, DerefMut)]
 struct AnimationTimer(Timer);
 
-fn reset_player(
-    mut player_q: Query<(&mut Transform, &mut Direction), With<Player>>,
-    window_q: Query<&Window>,
-) {
-    let (mut transform, mut direction) = player_q.single_mut();
-
-    let window = window_q.single();
-    let player_start = window.height() / 2. - 150. + 32.;
-
-    *transform = Transform::from_xyz(0., player_start, 1.);
-    direction.dir = types::Dir::Forward;
-}
-
 fn player_setup(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
-    query: Query<&Window>,
+    character: Res<Character>,
+    playfield: Res<Playfield>,
 ) {
-    let texture_handle = asset_server.load("Free/Main Characters/Ninja Frog/Idle (32x32).png");
+    let texture_handle = asset_server.load(character.idle_sheet());
     let texture_atlas =
         TextureAtlas::from_grid(texture_handle, Vec2::new(32.0, 32.0), 11, 1, None, None);
     let texture_atlas_handle = texture_atlases.add(texture_atlas);
 
-    let window = query.single();
-    let player_start = window.height() / 2. - 150. + 32.;
+    let player_start = playfield.water_line() + 32.;
 
     // Use only the subset of sprites in the sheet that make up the run animation
     let animation_indices = AnimationIndices { first: 0, last: 10 };
-    commands.spawn((
-        Player {},
-        Direction {
-            dir: types::Dir::Forward,
-        },
-        SpriteSheetBundle {
-            texture_atlas: texture_atlas_handle,
-            sprite: TextureAtlasSprite::new(animation_indices.first),
-            transform: Transform::from_xyz(0., player_start, 1.),
-            ..default()
-        },
-        animation_indices,
-        AnimationTimer(Timer::from_seconds(0.1, TimerMode::Repeating)),
-    ));
+    commands
+        .spawn((
+            Player {},
+            Direction {
+                dir: types::Dir::Forward,
+            },
+            SpriteSheetBundle {
+                texture_atlas: texture_atlas_handle,
+                sprite: TextureAtlasSprite::new(animation_indices.first),
+                transform: Transform::from_xyz(0., player_start, 1.),
+                ..default()
+            },
+            animation_indices,
+            AnimationTimer(Timer::from_seconds(0.1, TimerMode::Repeating)),
+            timestep::Interpolated::default(),
+            run::RunScoped {},
+        ))
+        .with_children(|parent| {
+            // a narrow strip straight down from the player, as far as they can reach
+            parent.spawn((
+                Reach::default(),
+                Collider::cuboid(4., REACH_DEPTH / 2.),
+                layers::reach(),
+                TransformBundle::from(Transform::from_xyz(0., -REACH_DEPTH / 2., 0.)),
+            ));
+        });
 }
 
 fn animate_sprites(
@@ -114,40 +220,35 @@ fn animate_sprites(
 }
 
 fn player_movement(
-    time: Res<Time>,
-    keys: Res<Input<KeyCode>>,
+    fixed_time: Res<FixedTime>,
+    input: Res<input::GameInput>,
+    tuning: Res<PlayerTuning>,
     mut player_pos_q: Query<&mut Transform, With<Player>>,
 ) {
     let mut trans = player_pos_q.single_mut();
-    let speed = 50.;
+    let distance = tuning.walk_speed * fixed_time.period.as_secs_f32();
 
-    if keys.pressed(KeyCode::Right) {
-        trans.translation.x += 50. * time.delta_seconds() * 3.;
+    if input.right {
+        trans.translation.x += distance;
     }
 
-    if keys.pressed(KeyCode::Left) {
-        trans.translation.x -= speed * time.delta_seconds() * 3.;
+    if input.left {
+        trans.translation.x -= distance;
     }
 }
 
 fn flip_player(
-    keys: Res<Input<KeyCode>>,
+    input: Res<input::GameInput>,
     mut query: Query<(&mut Transform, &mut Direction), With<Player>>,
 ) {
     let (mut transform, mut direction) = query.single_mut();
 
     match direction.dir {
-        types::Dir::Forward => {
-            if keys.pressed(KeyCode::Left) {
-                direction.dir = types::Dir::Backward;
-                transform.rotation = Quat::from_rotation_y(std::f32::consts::PI);
-            }
+        types::Dir::Forward if input.left => {
+            direction.turn(&mut transform, types::Dir::Backward);
         }
-        types::Dir::Backward => {
-            if keys.pressed(KeyCode::Right) {
-                direction.dir = types::Dir::Forward;
-                transform.rotation = Quat::default();
-            }
+        types::Dir::Backward if input.right => {
+            direction.turn(&mut transform, types::Dir::Forward);
         }
         // There are other directions, but they don't affect the player
         _ => {}
@@ -155,124 +256,212 @@ fn flip_player(
 }
 
 #[derive(Component)]
-struct Bomb;
+pub struct Bomb;
+
+/// How much the water carries bombs along
+pub const BOMB_DRIFT: f32 = 0.8;
+
+/// Bombs that have gone off, waiting to be dropped again
+pub type BombPool = Pool<(
+    Bomb,
+    current::Drift,
+    timestep::Interpolated,
+    particles::Trail,
+)>;
+
+/// Everything a bomb is made of, sinking from where `transform` puts it
+pub fn bomb_bundle(visuals: &Visuals, transform: Transform) -> impl Bundle {
+    (
+        Bomb {},
+        MaterialMesh2dBundle {
+            mesh: visuals.bomb_mesh.clone(),
+            material: visuals.bomb_material.clone(),
+            transform,
+            ..default()
+        },
+        current::Drift::new(BOMB_DRIFT),
+        Collider::ball(10.),
+        layers::bomb(),
+        timestep::Interpolated::default(),
+        run::RunScoped {},
+    )
+}
 
+#[allow(clippy::too_many_arguments)]
 fn bomb_drop(
     mut commands: Commands,
-    keys: Res<Input<KeyCode>>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
+    input: Res<input::GameInput>,
+    visuals: Res<Visuals>,
+    mut bombs: ResMut<BombPool>,
+    mut explosions: ResMut<ExplosionPool>,
     player_q: Query<&Transform, With<Player>>,
     bomb_q: Query<(Entity, &Transform), With<Bomb>>,
+    mut dropped: EventWriter<events::BombDropped>,
+    mut detonated: EventWriter<events::BombDetonated>,
 ) {
-    if keys.just_pressed(KeyCode::Space) {
+    if input.drop {
         match bomb_q.get_single() {
             // there's already a bomb, so detonate it
             Ok((entity, transform)) => {
-                detonate_bomb(commands, meshes, materials, entity, transform);
+                detonated.send(events::BombDetonated {
+                    position: transform.translation.truncate(),
+                });
+                explosions.spawn(&mut commands, explosion_bundle(&visuals, *transform));
+                bombs.release(&mut commands, entity);
             }
             // no bombs found from query, so drop one from the player
             Err(_) => {
                 let player_transform = player_q.single();
+                dropped.send(events::BombDropped {
+                    position: player_transform.translation.truncate(),
+                });
 
-                commands.spawn((
-                    Bomb {},
-                    MaterialMesh2dBundle {
-                        mesh: meshes.add(shape::Circle::new(12.).into()).into(),
-                        material: materials.add(ColorMaterial::from(Color::BLACK)),
-                        transform: *player_transform,
-                        ..default()
-                    },
-                    Collider::ball(10.),
-                ));
+                bombs.spawn(&mut commands, bomb_bundle(&visuals, *player_transform));
             }
         }
     }
 }
 
-fn bomb_movement(time: Res<Time>, mut bomb_q: Query<&mut Transform, With<Bomb>>) {
+fn bomb_movement(
+    fixed_time: Res<FixedTime>,
+    tuning: Res<PlayerTuning>,
+    rap_ctx: Res<RapierContext>,
+    mut bomb_q: Query<&mut Transform, With<Bomb>>,
+    terrain_q: Query<&tilemap::Terrain>,
+    ground_q: Query<&Transform, (With<level::Ground>, Without<Bomb>)>,
+    mut splashed: EventWriter<events::BombSplashed>,
+) {
     if let Ok(mut bomb) = bomb_q.get_single_mut() {
-        bomb.translation.y -= 50. * time.delta_seconds() * 3.;
+        // bombs come to rest once they sink onto the lake bed or a rock
+        let ray_pos = Vec2::new(bomb.translation.x, bomb.translation.y);
+        let is_terrain = |e: Entity| terrain_q.contains(e);
+        let filter = QueryFilter::default().predicate(&is_terrain);
+
+        if rap_ctx
+            .cast_ray(ray_pos, Vec2::new(0.0, -1.0), 12.0, true, filter)
+            .is_some()
+        {
+            return;
+        }
+
+        let last_y = bomb.translation.y;
+        bomb.translation.y -= tuning.bomb_sink_speed * fixed_time.period.as_secs_f32();
+
+        if let Ok(ground) = ground_q.get_single() {
+            let water_line = ground.translation.y;
+            if last_y >= water_line && bomb.translation.y < water_line {
+                splashed.send(events::BombSplashed {
+                    position: Vec2::new(bomb.translation.x, water_line),
+                });
+            }
+        }
     }
 }
 
+// how far below the player fish can be picked up from
+const REACH_DEPTH: f32 = 32.;
+
+/// Fish close enough below the player to be picked up
+#[derive(Component, Default)]
+pub struct Reach {
+    fish: Vec<Entity>,
+}
+
 #[derive(Component)]
 pub struct Explosion {
     timer: Timer,
+    /// Whether the explosion has killed anything yet
+    pub hit: bool,
 }
 
-fn detonate_bomb(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    entity: Entity,
-    trans: &Transform,
-) {
-    commands.spawn((
+/// Explosions that have burnt out, waiting to go off again
+pub type ExplosionPool = Pool<(Explosion,)>;
+
+fn explosion_bundle(visuals: &Visuals, transform: Transform) -> impl Bundle {
+    (
         Explosion {
             timer: Timer::new(Duration::from_millis(250), TimerMode::Once),
+            hit: false,
         },
         MaterialMesh2dBundle {
-            mesh: meshes.add(shape::Circle::new(25.).into()).into(),
-            material: materials.add(ColorMaterial::from(Color::ORANGE_RED)),
-            transform: *trans,
+            mesh: visuals.explosion_mesh.clone(),
+            material: visuals.explosion_material.clone(),
+            transform,
             ..default()
         },
         Collider::ball(23.),
-    ));
-
-    commands.entity(entity).despawn();
+        layers::explosion(),
+        run::RunScoped {},
+    )
 }
 
 // used to clear the explosion off screen after a certain time
 fn clear_explosion(
     mut commands: Commands,
-    time: Res<Time>,
+    fixed_time: Res<FixedTime>,
+    mut pool: ResMut<ExplosionPool>,
     mut q: Query<(Entity, &mut Explosion)>,
 ) {
     for (entity, mut explosion) in q.iter_mut() {
-        explosion.timer.tick(time.delta());
+        explosion.timer.tick(fixed_time.period);
         if explosion.timer.finished() {
-            commands.entity(entity).despawn_recursive();
+            pool.release(&mut commands, entity);
         }
     }
 }
 
-// called when the game ends to clear explosions from the scene
-fn clear_explosions(mut commands: Commands, mut q: Query<Entity, With<Explosion>>) {
-    for e in q.iter() {
-        commands.entity(e).despawn_recursive();
+// keeps up with which fish come into reach and which leave it
+fn track_reach(mut collisions: EventReader<CollisionEvent>, mut reach_q: Query<&mut Reach>) {
+    for event in collisions.iter() {
+        if let Some((reach, fish)) = layers::started(event, |e| reach_q.contains(e)) {
+            if let Ok(mut reach) = reach_q.get_mut(reach) {
+                reach.fish.push(fish);
+            }
+        } else if let Some((reach, fish)) = layers::stopped(event, |e| reach_q.contains(e)) {
+            if let Ok(mut reach) = reach_q.get_mut(reach) {
+                reach.fish.retain(|f| *f != fish);
+            }
+        }
     }
 }
 
-// fn grab_fish(mut commands: Commands) {}
-
 fn check_for_fish(
     mut commands: Commands,
-    keys: Res<Input<KeyCode>>,
-    rap_ctx: Res<RapierContext>,
-    mut score: ResMut<score::Score>,
-    fish_q: Query<&enemy::Fish>,
-    player_q: Query<&Transform, With<Player>>,
+    mut pool: ResMut<enemy::FishPool>,
+    input: Res<input::GameInput>,
+    mut collected: EventWriter<events::FishCollected>,
+    fish_q: Query<(&enemy::Fish, &Transform)>,
+    mut reach_q: Query<&mut Reach>,
 ) {
-    let player_trans = player_q.single();
-
-    // build the ray to cast
-    let ray_pos = Vec2::new(player_trans.translation.x, player_trans.translation.y);
-    let ray_dir = Vec2::new(0.0, -1.0);
-
-    let filter = QueryFilter::default();
-
-    if let Some((entity, toi)) = rap_ctx.cast_ray(ray_pos, ray_dir, 32.0, true, filter) {
-        let hit_point = ray_pos + ray_dir * toi;
-        info!("Entity {:?} hit at point {}", entity, hit_point);
-
-        if let Ok(fish) = fish_q.get(entity) {
-            // show the call to action ahove the player
-            if keys.just_pressed(KeyCode::A) {
-                score.val += enemy::get_score_for_fish_type(&fish.fish_type);
-                commands.entity(entity).despawn();
-            }
-        }
+    if !input.collect {
+        return;
     }
+
+    let Ok(mut reach) = reach_q.get_single_mut() else {
+        return;
+    };
+
+    // the one closest to the surface, right under the player
+    let nearest = reach
+        .fish
+        .iter()
+        .filter_map(|e| {
+            fish_q
+                .get(*e)
+                .ok()
+                .map(|(fish, transform)| (*e, fish, transform))
+        })
+        .max_by(|a, b| a.2.translation.y.total_cmp(&b.2.translation.y));
+
+    let Some((entity, fish, transform)) = nearest else {
+        return;
+    };
+
+    collected.send(events::FishCollected {
+        fish: entity,
+        fish_type: fish.fish_type,
+        position: transform.translation.truncate(),
+    });
+    pool.release(&mut commands, entity);
+    reach.fish.retain(|f| *f != entity);
 }
@@ -0,0 +1,53 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+pub struct PlayfieldPlugin;
+
+impl Plugin for PlayfieldPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Playfield>()
+            .add_system(fit_to_window.in_base_set(CoreSet::PreUpdate));
+    }
+}
+
+/// The area a run is played in, centred on the origin.
+/// Follows the window when there is one, and is set up by hand when running headless.
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct Playfield {
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Default for Playfield {
+    // the levels are laid out for this size
+    fn default() -> Self {
+        Playfield {
+            width: 1280.,
+            height: 720.,
+        }
+    }
+}
+
+impl Playfield {
+    /// Height of the water's surface, where the shore meets the lake
+    pub fn water_line(&self) -> f32 {
+        self.height / 2. - 150.
+    }
+}
+
+fn fit_to_window(
+    mut playfield: ResMut<Playfield>,
+    window_q: Query<&Window, (With<PrimaryWindow>, Changed<Window>)>,
+) {
+    let Ok(window) = window_q.get_single() else {
+        return;
+    };
+
+    let fitted = Playfield {
+        width: window.width(),
+        height: window.height(),
+    };
+
+    if *playfield != fitted {
+        *playfield = fitted;
+    }
+}
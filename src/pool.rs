@@ -0,0 +1,58 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use std::marker::PhantomData;
+
+/// Spare entities for something spawned over and over, like fish and explosions.
+///
+/// Releasing an entity takes `B`, the parts that make it what it is, back off it, then hides it
+/// and turns its collider off. Whatever it's drawn with stays, ready for the next one spawned.
+#[derive(Resource)]
+pub struct Pool<B: Bundle> {
+    free: Vec<Entity>,
+    parts: PhantomData<fn() -> B>,
+}
+
+impl<B: Bundle> Default for Pool<B> {
+    fn default() -> Self {
+        Pool {
+            free: vec![],
+            parts: PhantomData,
+        }
+    }
+}
+
+impl<B: Bundle> Pool<B> {
+    /// Puts `bundle` on a spare entity, or a new one if there aren't any
+    pub fn spawn(&mut self, commands: &mut Commands, bundle: impl Bundle) -> Entity {
+        match self.free.pop() {
+            Some(entity) => {
+                commands
+                    .entity(entity)
+                    .remove::<ColliderDisabled>()
+                    .insert(bundle);
+                entity
+            }
+            None => commands.spawn(bundle).id(),
+        }
+    }
+
+    /// Takes `entity` out of play, keeping it to be spawned again.
+    /// Releasing the same entity twice, say when two things finish it off in one tick, is fine.
+    pub fn release(&mut self, commands: &mut Commands, entity: Entity) {
+        if self.free.contains(&entity) {
+            return;
+        }
+
+        commands
+            .entity(entity)
+            .remove::<B>()
+            .insert((Visibility::Hidden, ColliderDisabled));
+        self.free.push(entity);
+    }
+
+    /// How many entities are waiting to be spawned again
+    pub fn spare(&self) -> usize {
+        self.free.len()
+    }
+}
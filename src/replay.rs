@@ -0,0 +1,442 @@
+use bevy::prelude::*;
+
+use std::path::PathBuf;
+
+use crate::input::{GameInput, InputSource};
+use crate::level;
+use crate::player;
+use crate::rng;
+use crate::run::{RunScoped, RunSet};
+use crate::savegame;
+use crate::sets::GameSet;
+use crate::state;
+use crate::stats;
+use crate::storage;
+use crate::timestep;
+use crate::transition;
+use crate::ui::theme::{TextRole, UiText};
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Recorder>()
+            .init_resource::<Playback>()
+            .add_event::<ReplayRequest>()
+            .add_startup_system(load_last_replay)
+            .add_system(handle_requests)
+            .add_systems(
+                (start_run, spawn_playback_hud)
+                    .chain()
+                    .in_set(RunSet::Setup)
+                    .in_schedule(OnEnter(state::AppState::Loading)),
+            )
+            .add_systems(
+                (
+                    record_input
+                        .in_set(GameSet::Scoring)
+                        .run_if(resource_equals(InputSource::Keyboard))
+                        .run_if(transition::idle),
+                    play_input
+                        .in_set(GameSet::Input)
+                        .run_if(resource_equals(InputSource::Replay))
+                        .run_if(transition::idle),
+                )
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
+            .add_systems(
+                (
+                    playback_controls.run_if(resource_equals(InputSource::Replay)),
+                    update_playback_hud.in_set(GameSet::Ui),
+                )
+                    .in_set(OnUpdate(state::AppState::Running)),
+            )
+            .add_system(reset_speed.in_schedule(OnExit(state::AppState::Running)))
+            .add_system(save_replay.in_schedule(OnEnter(state::AppState::GameOver)))
+            .add_system(stop_playback.in_schedule(OnEnter(state::AppState::MainMenu)));
+    }
+}
+
+const FILE_NAME: &str = "last_run.replay";
+const MAGIC: &[u8; 4] = b"GFRP";
+// bump this whenever the layout changes
+const VERSION: u8 = 2;
+
+const FAST_FORWARD_SPEED: f32 = 4.;
+
+/// Everything needed to play a run back: how it was set up and what was pressed each tick.
+///
+/// Gameplay moves in fixed ticks, so feeding the same input back tick by tick
+/// plays out exactly the same run.
+#[derive(Clone, Debug)]
+pub struct Recording {
+    pub seed: u64,
+    pub level: usize,
+    pub character: player::Character,
+    // `GameInput` packed with `GameInput::to_bits`, one for every tick
+    ticks: Vec<u8>,
+}
+
+impl Recording {
+    fn new(seed: u64, level: usize, character: player::Character) -> Self {
+        Recording {
+            seed,
+            level,
+            character,
+            ticks: vec![],
+        }
+    }
+
+    /// Packs the recording into a small binary file, a byte a tick
+    pub fn serialize(&self) -> Vec<u8> {
+        let character = player::Character::ALL
+            .iter()
+            .position(|c| *c == self.character)
+            .unwrap_or(0);
+
+        let mut bytes = Vec::with_capacity(19 + self.ticks.len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.push(self.level as u8);
+        bytes.push(character as u8);
+        bytes.extend_from_slice(&(self.ticks.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&self.ticks);
+
+        bytes
+    }
+
+    pub fn parse(bytes: &[u8]) -> Result<Recording, String> {
+        let Some(header) = bytes.get(..19) else {
+            return Err("replay is too short".to_string());
+        };
+
+        if &header[..4] != MAGIC {
+            return Err("not a replay file".to_string());
+        }
+
+        if header[4] != VERSION {
+            return Err(format!("replay has an unknown version ({})", header[4]));
+        }
+
+        let seed = u64::from_le_bytes(header[5..13].try_into().unwrap());
+        let level = header[13] as usize;
+        let character = *player::Character::ALL
+            .get(header[14] as usize)
+            .ok_or("replay has an unknown character")?;
+        let count = u32::from_le_bytes(header[15..19].try_into().unwrap()) as usize;
+
+        let body = &bytes[19..];
+        if body.len() != count {
+            return Err(format!(
+                "replay should have {} ticks but has {} bytes of them",
+                count,
+                body.len()
+            ));
+        }
+
+        Ok(Recording {
+            seed,
+            level,
+            character,
+            ticks: body.to_vec(),
+        })
+    }
+}
+
+/// The run that most recently finished, kept so it can be watched again
+#[derive(Resource, Default)]
+pub struct LastReplay {
+    pub recording: Option<Recording>,
+    path: Option<PathBuf>,
+}
+
+impl LastReplay {
+    fn save(&self) {
+        let (Some(path), Some(recording)) = (&self.path, &self.recording) else {
+            return;
+        };
+
+        if let Err(e) = storage::write_atomic(path, recording.serialize()) {
+            error!("couldn't save the replay to {:?}: {}", path, e);
+        }
+    }
+}
+
+// the live run being recorded
+#[derive(Resource, Default)]
+struct Recorder {
+    recording: Option<Recording>,
+}
+
+/// State of the replay being watched
+#[derive(Resource, Default)]
+pub struct Playback {
+    recording: Option<Recording>,
+    // next tick to play
+    tick: usize,
+    paused: bool,
+    fast_forward: bool,
+    // the level and character picked before the replay swapped in its own
+    restore: Option<(usize, player::Character)>,
+}
+
+/// Sent to start watching the last run, or to go back to playing
+pub enum ReplayRequest {
+    Watch,
+    Stop,
+}
+
+fn load_last_replay(mut commands: Commands) {
+    let path = storage::data_file(FILE_NAME);
+    let bytes = path.as_ref().and_then(|p| std::fs::read(p).ok());
+
+    let recording = bytes.and_then(|b| match Recording::parse(&b) {
+        Ok(recording) => Some(recording),
+        Err(e) => {
+            warn!("couldn't read the last replay: {}", e);
+            None
+        }
+    });
+
+    commands.insert_resource(LastReplay { recording, path });
+}
+
+fn handle_requests(
+    mut requests: EventReader<ReplayRequest>,
+    last: Res<LastReplay>,
+    mut playback: ResMut<Playback>,
+    mut source: ResMut<InputSource>,
+    mut selected_level: ResMut<level::SelectedLevel>,
+    mut character: ResMut<player::Character>,
+) {
+    for request in requests.iter() {
+        match request {
+            ReplayRequest::Watch => {
+                let Some(recording) = &last.recording else {
+                    continue;
+                };
+
+                // keep the player's own picks from before the first replay
+                let restore = playback
+                    .restore
+                    .take()
+                    .unwrap_or((selected_level.0, *character));
+
+                selected_level.0 = recording.level;
+                *character = recording.character;
+                *playback = Playback {
+                    recording: Some(recording.clone()),
+                    restore: Some(restore),
+                    ..default()
+                };
+                *source = InputSource::Replay;
+            }
+            ReplayRequest::Stop => {
+                stop(
+                    &mut playback,
+                    &mut source,
+                    &mut selected_level,
+                    &mut character,
+                );
+            }
+        }
+    }
+}
+
+fn stop(
+    playback: &mut Playback,
+    source: &mut InputSource,
+    selected_level: &mut level::SelectedLevel,
+    character: &mut player::Character,
+) {
+    if let Some((level, picked)) = playback.restore.take() {
+        selected_level.0 = level;
+        *character = picked;
+    }
+
+    *playback = Playback::default();
+    *source = InputSource::Keyboard;
+}
+
+fn stop_playback(
+    mut playback: ResMut<Playback>,
+    mut source: ResMut<InputSource>,
+    mut selected_level: ResMut<level::SelectedLevel>,
+    mut character: ResMut<player::Character>,
+) {
+    stop(
+        &mut playback,
+        &mut source,
+        &mut selected_level,
+        &mut character,
+    );
+}
+
+// replays start over from the recorded seed, live runs start a fresh recording
+fn start_run(
+    mut rng: ResMut<rng::GameRng>,
+    source: Res<InputSource>,
+    mut playback: ResMut<Playback>,
+    mut recorder: ResMut<Recorder>,
+    selected_level: Res<level::SelectedLevel>,
+    character: Res<player::Character>,
+    resume: Res<savegame::PendingResume>,
+) {
+    match *source {
+        InputSource::Replay => {
+            let recording = playback.recording.take();
+            if let Some(recording) = &recording {
+                *rng = rng::GameRng::new(recording.seed);
+            }
+
+            *playback = Playback {
+                recording,
+                restore: playback.restore.take(),
+                paused: playback.paused,
+                fast_forward: playback.fast_forward,
+                ..default()
+            };
+            recorder.recording = None;
+        }
+        // a continued run picks up part way, so there's no start to play it back from
+        InputSource::Keyboard if resume.0.is_some() => recorder.recording = None,
+        InputSource::Keyboard => {
+            recorder.recording = Some(Recording::new(rng.seed(), selected_level.0, *character));
+        }
+        // whoever scripts the input already has all of it
+        InputSource::Scripted => recorder.recording = None,
+    }
+}
+
+fn record_input(input: Res<GameInput>, mut recorder: ResMut<Recorder>) {
+    let Some(recording) = &mut recorder.recording else {
+        return;
+    };
+
+    recording.ticks.push(input.to_bits());
+}
+
+fn save_replay(mut recorder: ResMut<Recorder>, mut last: ResMut<LastReplay>) {
+    // a replay that was being watched has nothing new to save
+    let Some(recording) = recorder.recording.take() else {
+        return;
+    };
+
+    last.recording = Some(recording);
+    last.save();
+}
+
+fn play_input(
+    mut playback: ResMut<Playback>,
+    mut input: ResMut<GameInput>,
+    mut stats: ResMut<stats::RunStats>,
+    mut next_state: ResMut<NextState<state::AppState>>,
+) {
+    let playback = &mut *playback;
+    let Some(recording) = &playback.recording else {
+        return;
+    };
+
+    if let Some(bits) = recording.ticks.get(playback.tick) {
+        *input = GameInput::from_bits(*bits);
+        playback.tick += 1;
+    }
+
+    // the recorded run ended here
+    if playback.tick >= recording.ticks.len() {
+        if stats.end.is_none() {
+            stats.end = Some(stats::RunEnd::EndedEarly);
+        }
+        next_state.set(state::AppState::GameOver);
+    }
+}
+
+// Space pauses, F fast forwards and . steps a single tick while paused
+fn playback_controls(
+    keys: Res<Input<KeyCode>>,
+    mut playback: ResMut<Playback>,
+    mut time: ResMut<Time>,
+    mut fixed_time: ResMut<FixedTime>,
+) {
+    if keys.just_pressed(KeyCode::Space) {
+        playback.paused = !playback.paused;
+    }
+
+    if keys.just_pressed(KeyCode::F) {
+        playback.fast_forward = !playback.fast_forward;
+    }
+
+    // the clock stands still while paused, so a step hands it exactly one tick to run next frame
+    if playback.paused && keys.just_pressed(KeyCode::Period) {
+        fixed_time.tick(timestep::TICK);
+    }
+
+    let speed = if playback.paused {
+        0.
+    } else if playback.fast_forward {
+        FAST_FORWARD_SPEED
+    } else {
+        1.
+    };
+
+    if time.relative_speed() != speed {
+        time.set_relative_speed(speed);
+    }
+}
+
+fn reset_speed(mut time: ResMut<Time>) {
+    time.set_relative_speed(1.);
+}
+
+#[derive(Component)]
+struct PlaybackHud {}
+
+fn playback_label(playback: &Playback) -> String {
+    let mode = if playback.paused {
+        "Paused, . to step"
+    } else if playback.fast_forward {
+        "Fast forward"
+    } else {
+        "Playing"
+    };
+
+    format!("Replay - {}", mode)
+}
+
+fn spawn_playback_hud(mut commands: Commands, source: Res<InputSource>, playback: Res<Playback>) {
+    if *source != InputSource::Replay {
+        return;
+    }
+
+    commands.spawn((
+        PlaybackHud {},
+        RunScoped {},
+        UiText::new(TextRole::Small, playback_label(&playback)),
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    bottom: Val::Px(20.),
+                    left: Val::Px(20.),
+                    ..default()
+                },
+                ..default()
+            },
+            ..default()
+        },
+    ));
+}
+
+fn update_playback_hud(playback: Res<Playback>, mut hud_q: Query<&mut UiText, With<PlaybackHud>>) {
+    if !playback.is_changed() {
+        return;
+    }
+
+    let label = playback_label(&playback);
+    for mut text in hud_q.iter_mut() {
+        if text.value != label {
+            text.value = label.clone();
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use bevy::prelude::*;
+use rand::{Error, Rng, RngCore};
+
+use crate::run::RunSet;
+use crate::state;
+
+pub struct RngPlugin;
+
+impl Plugin for RngPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(GameRng::new(0))
+            .init_resource::<NextSeed>()
+            .add_system(
+                reseed
+                    .in_set(RunSet::Reset)
+                    .in_schedule(OnEnter(state::AppState::Loading)),
+            );
+    }
+}
+
+/// The random number generator behind everything that changes how a run plays out.
+/// Each run gets its own seed so it can be told apart, and so it can be played again.
+#[derive(Resource)]
+pub struct GameRng {
+    seed: u64,
+    state: u64,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        GameRng { seed, state: seed }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Where the generator has got to, so a saved run can carry on from the same place
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+
+    pub fn resume(seed: u64, state: u64) -> Self {
+        GameRng { seed, state }
+    }
+}
+
+// splitmix64, small and fast with all of its state in a single u64
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// A seed for the next run to use instead of a random one, used up once that run starts
+#[derive(Resource, Default)]
+pub struct NextSeed(pub Option<u64>);
+
+pub fn reseed(mut rng: ResMut<GameRng>, mut next: ResMut<NextSeed>) {
+    let seed = next.0.take().unwrap_or_else(|| rand::thread_rng().gen());
+    *rng = GameRng::new(seed);
+}
@@ -0,0 +1,92 @@
+use bevy::prelude::*;
+
+use crate::state::AppState;
+
+/// Tears a run down and sets the next one up.
+///
+/// Everything spawned for a run is marked `RunScoped` and despawned as soon as the run is left,
+/// whether it ended, was restarted or was quit. Entering `AppState::Loading` then goes through
+/// `RunSet` in order, so the last run is gone and every run resource is back to how it started
+/// before anything new is spawned.
+pub struct RunPlugin;
+
+impl Plugin for RunPlugin {
+    fn build(&self, app: &mut App) {
+        app.edit_schedule(OnEnter(AppState::Loading), |schedule| {
+            schedule.configure_sets((RunSet::Teardown, RunSet::Reset, RunSet::Setup).chain());
+        });
+
+        app.add_run_teardown(despawn_run_entities);
+    }
+}
+
+// entering any of these leaves whatever run was going on
+const RUN_EXITS: [AppState; 3] = [AppState::Loading, AppState::GameOver, AppState::MainMenu];
+
+/// The steps of starting a run, in this order
+#[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone, Copy)]
+pub enum RunSet {
+    /// Whatever is left of the last run is despawned
+    Teardown,
+    /// Run resources go back to how they start
+    Reset,
+    /// The lake, the player and the HUD are spawned
+    Setup,
+}
+
+/// Belongs to a single run and is despawned when the run is left
+#[derive(Component)]
+pub struct RunScoped {}
+
+/// Registers resources that only last for a run
+pub trait RunApp {
+    /// Adds a resource that's made afresh, with `FromWorld`, at the start of every run
+    fn init_run_resource<R: Resource + FromWorld>(&mut self) -> &mut Self;
+
+    /// Adds a pool of `RunScoped` entities, like `enemy::FishPool`, emptied whenever they're
+    /// torn down
+    fn init_run_pool<P: Resource + FromWorld>(&mut self) -> &mut Self;
+
+    /// Adds a system that cleans up after a run in `RunSet::Teardown`, whenever a run is left
+    fn add_run_teardown<M>(&mut self, system: impl IntoSystemConfig<M> + Clone) -> &mut Self;
+}
+
+impl RunApp for App {
+    fn init_run_resource<R: Resource + FromWorld>(&mut self) -> &mut Self {
+        self.init_resource::<R>().add_system(
+            reset_resource::<R>
+                .in_set(RunSet::Reset)
+                .in_schedule(OnEnter(AppState::Loading)),
+        )
+    }
+
+    fn init_run_pool<P: Resource + FromWorld>(&mut self) -> &mut Self {
+        // the pool can't keep hold of entities that are about to be despawned
+        self.init_resource::<P>()
+            .add_run_teardown(reset_resource::<P>)
+    }
+
+    fn add_run_teardown<M>(&mut self, system: impl IntoSystemConfig<M> + Clone) -> &mut Self {
+        for state in RUN_EXITS {
+            self.add_system(
+                system
+                    .clone()
+                    .in_set(RunSet::Teardown)
+                    .in_schedule(OnEnter(state)),
+            );
+        }
+
+        self
+    }
+}
+
+fn reset_resource<R: Resource + FromWorld>(world: &mut World) {
+    let resource = R::from_world(world);
+    world.insert_resource(resource);
+}
+
+fn despawn_run_entities(mut commands: Commands, q: Query<Entity, With<RunScoped>>) {
+    for e in q.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+}
@@ -0,0 +1,561 @@
+use bevy::{app::AppExit, prelude::*};
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::achievement;
+use crate::current;
+use crate::enemy;
+use crate::input;
+use crate::level;
+use crate::menu;
+use crate::player;
+use crate::rng;
+use crate::score;
+use crate::state;
+use crate::stats;
+use crate::storage;
+use crate::types;
+use crate::visuals::Visuals;
+
+pub struct SaveGamePlugin;
+
+impl Plugin for SaveGamePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PendingResume>()
+            .add_event::<SaveRun>()
+            .add_event::<ContinueRun>()
+            .add_startup_system(load_saved_run)
+            .add_system(continue_run)
+            // last, so a quit sent from anywhere in the frame is caught before the app closes
+            .add_system(save_run.in_base_set(CoreSet::Last))
+            .add_system(
+                restore_run
+                    .run_if(resume_pending)
+                    .in_schedule(OnEnter(state::AppState::Running)),
+            );
+    }
+}
+
+const FILE_NAME: &str = "run.txt";
+const HEADER: &str = "gonefishin-run";
+// bump this whenever the line format changes, and teach `RunSnapshot::parse` the old one
+const VERSION: u32 = 2;
+
+/// Sent to save the run being played so it can be continued later
+pub struct SaveRun;
+
+/// Sent to pick the saved run back up
+pub struct ContinueRun;
+
+/// A fish as it was when the run was saved
+#[derive(Clone, Debug, PartialEq)]
+pub struct SavedFish {
+    pub fish_type: enemy::FishType,
+    pub direction: types::Dir,
+    pub wave: u32,
+    pub position: Vec2,
+    pub drift: Vec2,
+}
+
+/// Everything needed to carry on a run exactly where it was left.
+/// Explosions are over in a quarter of a second, so one going off is left out.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RunSnapshot {
+    pub seed: u64,
+    pub rng_state: u64,
+    pub level: usize,
+    pub character: player::Character,
+    pub score: u32,
+    pub combo: u32,
+    pub combo_elapsed: Duration,
+    pub spawn_elapsed: Duration,
+    pub wave: u32,
+    pub spawned: u32,
+    pub cleared: u32,
+    pub escaped: u32,
+    pub bombs_used: u32,
+    pub bombs_hit: u32,
+    pub duration: Duration,
+    /// Killed and collected counts by fish type
+    pub species: Vec<(enemy::FishType, stats::SpeciesStats)>,
+    pub player_position: Vec2,
+    pub player_facing: types::Dir,
+    pub fish: Vec<SavedFish>,
+    /// Position and drift of the bomb in the water, if there is one
+    pub bomb: Option<(Vec2, Vec2)>,
+}
+
+fn fish_type_name(fish_type: enemy::FishType) -> String {
+    format!("{:?}", fish_type)
+}
+
+fn parse_fish_type(name: &str) -> Result<enemy::FishType, String> {
+    enemy::FishType::ALL
+        .into_iter()
+        .find(|t| fish_type_name(*t) == name)
+        .ok_or_else(|| format!("unknown fish type {:?}", name))
+}
+
+fn parse_dir(name: &str) -> Result<types::Dir, String> {
+    [types::Dir::Forward, types::Dir::Backward, types::Dir::Up]
+        .into_iter()
+        .find(|d| format!("{:?}", d) == name)
+        .ok_or_else(|| format!("unknown direction {:?}", name))
+}
+
+fn parse_num<T: std::str::FromStr>(field: &str) -> Result<T, String> {
+    field
+        .parse()
+        .map_err(|_| format!("{:?} isn't a number", field))
+}
+
+fn parse_nanos(field: &str) -> Result<Duration, String> {
+    parse_num(field).map(Duration::from_nanos)
+}
+
+fn parse_vec(x: &str, y: &str) -> Result<Vec2, String> {
+    Ok(Vec2::new(parse_num(x)?, parse_num(y)?))
+}
+
+impl RunSnapshot {
+    pub fn serialize(&self) -> String {
+        let character = player::Character::ALL
+            .iter()
+            .position(|c| *c == self.character)
+            .unwrap_or(0);
+
+        let mut lines = vec![
+            format!("{} {}", HEADER, VERSION),
+            format!("rng\t{}\t{}", self.seed, self.rng_state),
+            format!("level\t{}", self.level),
+            format!("character\t{}", character),
+            format!("score\t{}", self.score),
+            format!("combo\t{}\t{}", self.combo, self.combo_elapsed.as_nanos()),
+            format!(
+                "spawner\t{}\t{}\t{}\t{}",
+                self.spawn_elapsed.as_nanos(),
+                self.wave,
+                self.spawned,
+                self.cleared
+            ),
+            format!(
+                "stats\t{}\t{}\t{}\t{}",
+                self.escaped,
+                self.bombs_used,
+                self.bombs_hit,
+                self.duration.as_nanos()
+            ),
+            format!(
+                "player\t{}\t{}\t{:?}",
+                self.player_position.x, self.player_position.y, self.player_facing
+            ),
+        ];
+
+        for (fish_type, species) in &self.species {
+            lines.push(format!(
+                "species\t{}\t{}\t{}",
+                fish_type_name(*fish_type),
+                species.killed,
+                species.collected
+            ));
+        }
+
+        // floats are written in full so they come back exactly the same
+        for fish in &self.fish {
+            lines.push(format!(
+                "fish\t{}\t{:?}\t{}\t{}\t{}\t{}\t{}",
+                fish_type_name(fish.fish_type),
+                fish.direction,
+                fish.wave,
+                fish.position.x,
+                fish.position.y,
+                fish.drift.x,
+                fish.drift.y
+            ));
+        }
+
+        if let Some((position, drift)) = self.bomb {
+            lines.push(format!(
+                "bomb\t{}\t{}\t{}\t{}",
+                position.x, position.y, drift.x, drift.y
+            ));
+        }
+
+        // a save cut off partway through never has this last line
+        lines.push("end".to_string());
+
+        lines.join("\n") + "\n"
+    }
+
+    /// Reads a saved run, refusing the whole thing if any of it doesn't make sense,
+    /// since a run that is only partly restored wouldn't be the same run.
+    pub fn parse(contents: &str) -> Result<RunSnapshot, String> {
+        let mut lines = contents.lines();
+
+        let version = lines
+            .next()
+            .and_then(|l| l.strip_prefix(HEADER))
+            .ok_or("not a saved run")?;
+
+        // older versions get migrated here once there are any. Version 1 had no end line, so a
+        // whole one can't be told apart from one that was cut off
+        match version.trim().parse::<u32>() {
+            Ok(VERSION) => {}
+            Ok(v) if v > VERSION => {
+                return Err(format!("the run was saved by a newer version ({v})"))
+            }
+            _ => return Err(format!("unknown save version {:?}", version.trim())),
+        }
+
+        let mut snapshot = RunSnapshot {
+            seed: 0,
+            rng_state: 0,
+            level: 0,
+            character: player::Character::default(),
+            score: 0,
+            combo: 0,
+            combo_elapsed: Duration::ZERO,
+            spawn_elapsed: Duration::ZERO,
+            wave: 1,
+            spawned: 0,
+            cleared: 0,
+            escaped: 0,
+            bombs_used: 0,
+            bombs_hit: 0,
+            duration: Duration::ZERO,
+            species: vec![],
+            player_position: Vec2::ZERO,
+            player_facing: types::Dir::Forward,
+            fish: vec![],
+            bomb: None,
+        };
+        let mut seen_rng = false;
+        let mut seen_player = false;
+        let mut seen_end = false;
+
+        for line in lines.filter(|l| !l.trim().is_empty()) {
+            if seen_end {
+                return Err(format!("unreadable line {:?}", line));
+            }
+
+            let fields: Vec<&str> = line.split('\t').collect();
+
+            match fields.as_slice() {
+                ["rng", seed, state] => {
+                    snapshot.seed = parse_num(seed)?;
+                    snapshot.rng_state = parse_num(state)?;
+                    seen_rng = true;
+                }
+                ["level", level] => {
+                    snapshot.level = parse_num(level)?;
+                    if snapshot.level >= level::LEVELS.len() {
+                        return Err(format!("there's no level {}", snapshot.level));
+                    }
+                }
+                ["character", index] => {
+                    snapshot.character = *player::Character::ALL
+                        .get(parse_num::<usize>(index)?)
+                        .ok_or("unknown character")?;
+                }
+                ["score", score] => snapshot.score = parse_num(score)?,
+                ["combo", count, elapsed] => {
+                    snapshot.combo = parse_num(count)?;
+                    snapshot.combo_elapsed = parse_nanos(elapsed)?;
+                }
+                ["spawner", elapsed, wave, spawned, cleared] => {
+                    snapshot.spawn_elapsed = parse_nanos(elapsed)?;
+                    snapshot.wave = parse_num(wave)?;
+                    snapshot.spawned = parse_num(spawned)?;
+                    snapshot.cleared = parse_num(cleared)?;
+                }
+                ["stats", escaped, bombs_used, bombs_hit, duration] => {
+                    snapshot.escaped = parse_num(escaped)?;
+                    snapshot.bombs_used = parse_num(bombs_used)?;
+                    snapshot.bombs_hit = parse_num(bombs_hit)?;
+                    snapshot.duration = parse_nanos(duration)?;
+                }
+                ["player", x, y, facing] => {
+                    snapshot.player_position = parse_vec(x, y)?;
+                    snapshot.player_facing = parse_dir(facing)?;
+                    seen_player = true;
+                }
+                ["species", fish_type, killed, collected] => snapshot.species.push((
+                    parse_fish_type(fish_type)?,
+                    stats::SpeciesStats {
+                        killed: parse_num(killed)?,
+                        collected: parse_num(collected)?,
+                    },
+                )),
+                ["fish", fish_type, direction, wave, x, y, drift_x, drift_y] => {
+                    snapshot.fish.push(SavedFish {
+                        fish_type: parse_fish_type(fish_type)?,
+                        direction: parse_dir(direction)?,
+                        wave: parse_num(wave)?,
+                        position: parse_vec(x, y)?,
+                        drift: parse_vec(drift_x, drift_y)?,
+                    })
+                }
+                ["bomb", x, y, drift_x, drift_y] => {
+                    snapshot.bomb = Some((parse_vec(x, y)?, parse_vec(drift_x, drift_y)?));
+                }
+                ["end"] => seen_end = true,
+                _ => return Err(format!("unreadable line {:?}", line)),
+            }
+        }
+
+        if !seen_rng || !seen_player || !seen_end {
+            return Err("the save is missing part of the run".to_string());
+        }
+
+        Ok(snapshot)
+    }
+}
+
+/// The run saved on disk, if there's one that can be continued
+#[derive(Resource, Default)]
+pub struct SavedRun {
+    pub snapshot: Option<RunSnapshot>,
+    path: Option<PathBuf>,
+}
+
+impl SavedRun {
+    fn save(&self) {
+        let (Some(path), Some(snapshot)) = (&self.path, &self.snapshot) else {
+            return;
+        };
+
+        if let Err(e) = storage::write_atomic(path, snapshot.serialize()) {
+            error!("couldn't save the run to {:?}: {}", path, e);
+        }
+    }
+
+    // a run can only be continued once
+    fn remove(&mut self) {
+        self.snapshot = None;
+
+        if let Some(path) = &self.path {
+            if let Err(e) = std::fs::remove_file(path) {
+                warn!("couldn't remove the saved run {:?}: {}", path, e);
+            }
+        }
+    }
+}
+
+/// The run being continued, put back in place once it's running again
+#[derive(Resource, Default)]
+pub struct PendingResume(pub Option<RunSnapshot>);
+
+fn resume_pending(pending: Res<PendingResume>) -> bool {
+    pending.0.is_some()
+}
+
+fn load_saved_run(mut commands: Commands) {
+    let path = storage::data_file(FILE_NAME);
+    let contents = path.as_ref().and_then(|p| std::fs::read_to_string(p).ok());
+
+    // a save that can't be read is left alone rather than overwritten
+    let snapshot = contents.and_then(|c| match RunSnapshot::parse(&c) {
+        Ok(snapshot) => Some(snapshot),
+        Err(e) => {
+            warn!("can't continue the saved run: {}", e);
+            None
+        }
+    });
+
+    commands.insert_resource(SavedRun { snapshot, path });
+}
+
+fn run_in_progress(app_state: &state::AppState, back_target: &menu::BackTarget) -> bool {
+    match app_state {
+        state::AppState::Running | state::AppState::Paused => true,
+        // settings opened from the pause menu
+        state::AppState::Settings => back_target.0 == state::AppState::Paused,
+        _ => false,
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn save_run(
+    mut requests: EventReader<SaveRun>,
+    mut exit: EventReader<AppExit>,
+    app_state: Res<State<state::AppState>>,
+    back_target: Res<menu::BackTarget>,
+    source: Res<input::InputSource>,
+    mut saved: ResMut<SavedRun>,
+    game_rng: Res<rng::GameRng>,
+    selected_level: Res<level::SelectedLevel>,
+    character: Res<player::Character>,
+    score: Res<score::Score>,
+    combo: Res<score::Combo>,
+    config: Option<Res<enemy::FishSpawnConfig>>,
+    run_stats: Res<stats::RunStats>,
+    player_q: Query<(&Transform, &player::Direction), With<player::Player>>,
+    fish_q: Query<(&enemy::Fish, &Transform, &current::Drift)>,
+    bomb_q: Query<(&Transform, &current::Drift), With<player::Bomb>>,
+) {
+    let requested = requests.iter().count() > 0;
+    let quitting = exit.iter().count() > 0;
+
+    if !(requested || quitting) || !run_in_progress(&app_state.0, &back_target) {
+        return;
+    }
+
+    // a replay isn't a run of its own
+    if *source == input::InputSource::Replay {
+        return;
+    }
+
+    let (Some(config), Ok((player_transform, facing))) = (config, player_q.get_single()) else {
+        return;
+    };
+
+    let mut species: Vec<_> = run_stats.species.iter().map(|(t, s)| (*t, *s)).collect();
+    species.sort_by_key(|(t, _)| fish_type_name(*t));
+
+    saved.snapshot = Some(RunSnapshot {
+        seed: game_rng.seed(),
+        rng_state: game_rng.state(),
+        level: selected_level.0,
+        character: *character,
+        score: score.val,
+        combo: combo.count,
+        combo_elapsed: combo.timer.elapsed(),
+        spawn_elapsed: config.timer.elapsed(),
+        wave: config.wave,
+        spawned: config.spawned,
+        cleared: config.cleared,
+        escaped: run_stats.escaped,
+        bombs_used: run_stats.bombs_used,
+        bombs_hit: run_stats.bombs_hit,
+        duration: run_stats.duration,
+        species,
+        player_position: player_transform.translation.truncate(),
+        player_facing: facing.dir,
+        fish: fish_q
+            .iter()
+            .map(|(fish, transform, drift)| SavedFish {
+                fish_type: fish.fish_type,
+                direction: fish.direction,
+                wave: fish.wave,
+                position: transform.translation.truncate(),
+                drift: drift.velocity,
+            })
+            .collect(),
+        bomb: bomb_q
+            .get_single()
+            .ok()
+            .map(|(transform, drift)| (transform.translation.truncate(), drift.velocity)),
+    });
+
+    saved.save();
+    info!("saved the run");
+}
+
+// sets the run's lake and character up before loading it like any other run
+fn continue_run(
+    mut requests: EventReader<ContinueRun>,
+    mut saved: ResMut<SavedRun>,
+    mut pending: ResMut<PendingResume>,
+    mut selected_level: ResMut<level::SelectedLevel>,
+    mut character: ResMut<player::Character>,
+    mut next_state: ResMut<NextState<state::AppState>>,
+) {
+    if requests.iter().count() == 0 {
+        return;
+    }
+
+    let Some(snapshot) = saved.snapshot.clone() else {
+        return;
+    };
+
+    saved.remove();
+    selected_level.0 = snapshot.level;
+    *character = snapshot.character;
+    pending.0 = Some(snapshot);
+    next_state.set(state::AppState::Loading);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn restore_run(
+    mut commands: Commands,
+    visuals: Res<Visuals>,
+    mut fish_pool: ResMut<enemy::FishPool>,
+    mut bomb_pool: ResMut<player::BombPool>,
+    mut pending: ResMut<PendingResume>,
+    mut game_rng: ResMut<rng::GameRng>,
+    mut score: ResMut<score::Score>,
+    mut combo: ResMut<score::Combo>,
+    mut config: ResMut<enemy::FishSpawnConfig>,
+    mut run_stats: ResMut<stats::RunStats>,
+    mut progress: ResMut<achievement::RunProgress>,
+    mut player_q: Query<(&mut Transform, &mut player::Direction), With<player::Player>>,
+) {
+    let Some(snapshot) = pending.0.take() else {
+        return;
+    };
+
+    *game_rng = rng::GameRng::resume(snapshot.seed, snapshot.rng_state);
+
+    // the score on screen counts up to where it was by itself
+    score.val = snapshot.score;
+
+    combo.count = snapshot.combo;
+    combo.timer.set_elapsed(snapshot.combo_elapsed);
+
+    config.timer.set_elapsed(snapshot.spawn_elapsed);
+    config.wave = snapshot.wave;
+    config.spawned = snapshot.spawned;
+    config.cleared = snapshot.cleared;
+    progress.waves_cleared = snapshot.cleared;
+
+    run_stats.escaped = snapshot.escaped;
+    run_stats.bombs_used = snapshot.bombs_used;
+    run_stats.bombs_hit = snapshot.bombs_hit;
+    run_stats.duration = snapshot.duration;
+    for (fish_type, species) in &snapshot.species {
+        *run_stats.species_mut(*fish_type) = *species;
+    }
+
+    if let Ok((mut transform, mut direction)) = player_q.get_single_mut() {
+        transform.translation.x = snapshot.player_position.x;
+        transform.translation.y = snapshot.player_position.y;
+        direction.turn(&mut transform, snapshot.player_facing);
+    }
+
+    for saved_fish in &snapshot.fish {
+        let fish = enemy::Fish {
+            fish_type: saved_fish.fish_type,
+            direction: saved_fish.direction,
+            wave: saved_fish.wave,
+        };
+
+        let entity = fish_pool.spawn(
+            &mut commands,
+            enemy::fish_bundle(
+                &visuals,
+                fish.fish_type,
+                fish.direction,
+                saved_fish.position,
+                fish.wave,
+            ),
+        );
+        commands.entity(entity).insert(current::Drift {
+            velocity: saved_fish.drift,
+            ..fish.drift()
+        });
+    }
+
+    if let Some((position, drift)) = snapshot.bomb {
+        let entity = bomb_pool.spawn(
+            &mut commands,
+            player::bomb_bundle(&visuals, Transform::from_xyz(position.x, position.y, 1.)),
+        );
+        commands.entity(entity).insert(current::Drift {
+            velocity: drift,
+            ..current::Drift::new(player::BOMB_DRIFT)
+        });
+    }
+
+    info!("continued the saved run");
+}
@@ -1,56 +1,147 @@
 use bevy::prelude::*;
 
+use std::time::Duration;
+
+use crate::enemy;
+use crate::events;
+use crate::run::{RunApp, RunScoped, RunSet};
+use crate::sets::GameSet;
 use crate::state;
+use crate::ui::theme::{TextRole, UiText};
 
 pub struct ScorePlugin;
 
 impl Plugin for ScorePlugin {
     fn build(&self, app: &mut App) {
-        app.add_startup_system(setup_score)
-            .insert_resource(Score { val: 0 })
-            .add_system(update_score)
-            .add_system(reset_score.in_schedule(OnExit(state::AppState::GameOver)));
+        app.init_run_resource::<Score>()
+            .init_run_resource::<Combo>()
+            .add_system(
+                setup_score
+                    .in_set(RunSet::Setup)
+                    .in_schedule(OnEnter(state::AppState::Loading)),
+            )
+            .add_systems(
+                (tick_combo, score_collected_fish)
+                    .chain()
+                    .in_set(GameSet::Scoring)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
+            .add_system(count_score.in_set(GameSet::Ui));
     }
 }
 
-#[derive(Resource)]
+// how soon after the last catch the next one has to come to keep a combo going
+const COMBO_WINDOW: Duration = Duration::from_millis(2000);
+// how quickly the number on screen catches up with the real score
+const COUNT_SPEED: f32 = 8.;
+
+#[derive(Resource, Default)]
 pub struct Score {
     pub val: u32,
 }
 
+/// Fish brought in one after the other without a long gap
+#[derive(Resource)]
+pub struct Combo {
+    pub count: u32,
+    /// Runs out when the next catch comes too late
+    pub timer: Timer,
+}
+
+impl Default for Combo {
+    fn default() -> Self {
+        Combo {
+            count: 0,
+            timer: Timer::new(COMBO_WINDOW, TimerMode::Once),
+        }
+    }
+}
+
+// the number on screen counts up to the score rather than jumping to it
 #[derive(Component)]
-struct ScoreText {}
+struct ScoreText {
+    shown: f32,
+}
 
-fn setup_score(mut commands: Commands, asset_server: Res<AssetServer>) {
+/// Points for bringing in a fish of the given type
+pub fn points_for(fish_type: enemy::FishType) -> u32 {
+    match fish_type {
+        enemy::FishType::Basic => 100,
+        _ => 0,
+    }
+}
+
+fn tick_combo(fixed_time: Res<FixedTime>, mut combo: ResMut<Combo>) {
+    combo.timer.tick(fixed_time.period);
+    if combo.timer.just_finished() {
+        combo.count = 0;
+    }
+}
+
+fn score_collected_fish(
+    mut collected: EventReader<events::FishCollected>,
+    mut score: ResMut<Score>,
+    mut combo: ResMut<Combo>,
+    mut changed: EventWriter<events::ScoreChanged>,
+) {
+    for fish in collected.iter() {
+        combo.count += 1;
+        combo.timer.reset();
+
+        let points = points_for(fish.fish_type);
+        score.val += points;
+
+        changed.send(events::ScoreChanged {
+            delta: points as i32,
+            position: Some(fish.position),
+            combo: combo.count,
+        });
+    }
+}
+
+fn setup_score(mut commands: Commands) {
     commands.spawn((
-        ScoreText {},
-        TextBundle::from_section(
-            "0",
-            TextStyle {
-                font: asset_server.load("fonts/OpenSans.ttf"),
-                font_size: 45.,
-                color: Color::BLACK,
-            },
-        )
-        .with_text_alignment(TextAlignment::Right)
-        .with_style(Style {
-            position_type: PositionType::Absolute,
-            position: UiRect {
-                top: Val::Px(20.),
-                right: Val::Px(20.),
+        ScoreText { shown: 0. },
+        RunScoped {},
+        UiText::new(TextRole::Title, "0"),
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(20.),
+                    right: Val::Px(20.),
+                    ..default()
+                },
                 ..default()
             },
             ..default()
-        }),
+        },
     ));
 }
 
-fn update_score(score: Res<Score>, mut text_q: Query<&mut Text, With<ScoreText>>) {
-    let mut text = text_q.single_mut();
+fn count_score(
+    time: Res<Time>,
+    score: Res<Score>,
+    mut text_q: Query<(&mut ScoreText, &mut UiText)>,
+) {
+    // the score is only on screen during a run
+    let Ok((mut counter, mut text)) = text_q.get_single_mut() else {
+        return;
+    };
 
-    text.sections[0].value = format!("{}", score.val);
-}
+    let target = score.val as f32;
+    if counter.shown == target {
+        return;
+    }
 
-fn reset_score(mut score: ResMut<Score>) {
-    score.val = 0;
+    counter.shown += (target - counter.shown) * (time.delta_seconds() * COUNT_SPEED).min(1.);
+    if (target - counter.shown).abs() < 1. {
+        counter.shown = target;
+    }
+
+    // the text is rebuilt whenever it changes, so only touch it when it has to
+    let value = format!("{}", counter.shown.round() as u32);
+    if text.value != value {
+        text.value = value;
+    }
 }
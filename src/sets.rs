@@ -0,0 +1,22 @@
+use bevy::prelude::*;
+
+/// The steps a run goes through, in this order. Every plugin puts its run systems in one of
+/// these rather than ordering against other plugins' systems directly.
+///
+/// `Input` up to `Scoring` make up each tick in `CoreSchedule::FixedUpdate` and only run while
+/// a run is playing, with rapier's `PhysicsSet`s stepping between `Movement` and `Combat`.
+/// Outside the tick, `Input` is also where the keyboard gets read, and `Scoring` and `Ui` order
+/// the frame's reactions to what happened during its ticks.
+#[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone, Copy)]
+pub enum GameSet {
+    /// Fills in `GameInput` for the tick
+    Input,
+    /// The player, fish, bombs and currents move
+    Movement,
+    /// Bombs go off, fish get hit and caught, using this tick's contacts
+    Combat,
+    /// Points, combos, waves and run stats from what combat did
+    Scoring,
+    /// Everything drawn on top of the run, once per frame after all its ticks
+    Ui,
+}
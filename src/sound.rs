@@ -0,0 +1,290 @@
+use bevy::{asset::LoadState, prelude::*};
+
+use crate::events;
+use crate::state::AppState;
+use crate::storage;
+use crate::ui::focus;
+
+/// Sound effects for what happens in a run, music for each screen, and the volume settings.
+/// Named so it doesn't clash with bevy's own `AudioPlugin`.
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Sounds>()
+            .init_resource::<MusicPlayer>()
+            .add_startup_system(load_volume)
+            .add_system(play_effects.after(focus::FocusSet))
+            .add_system(play_music)
+            .add_system(apply_music_volume.after(play_music));
+    }
+}
+
+const FILE_NAME: &str = "settings.txt";
+const HEADER: &str = "gonefishin-settings";
+const VERSION: u32 = 1;
+
+/// How much each settings button press moves a volume by
+pub const VOLUME_STEP: f32 = 0.1;
+
+#[derive(Resource)]
+pub struct Sounds {
+    bomb_drop: Handle<AudioSource>,
+    splash: Handle<AudioSource>,
+    detonate: Handle<AudioSource>,
+    fish_kill: Handle<AudioSource>,
+    collect: Handle<AudioSource>,
+    turtle_hit: Handle<AudioSource>,
+    click: Handle<AudioSource>,
+    music_menu: Handle<AudioSource>,
+    music_game: Handle<AudioSource>,
+    music_game_over: Handle<AudioSource>,
+}
+
+impl FromWorld for Sounds {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+
+        Sounds {
+            bomb_drop: asset_server.load("audio/bomb_drop.wav"),
+            splash: asset_server.load("audio/splash.wav"),
+            detonate: asset_server.load("audio/detonate.wav"),
+            fish_kill: asset_server.load("audio/fish_kill.wav"),
+            collect: asset_server.load("audio/collect.wav"),
+            turtle_hit: asset_server.load("audio/turtle_hit.wav"),
+            click: asset_server.load("audio/click.wav"),
+            music_menu: asset_server.load("audio/music_menu.wav"),
+            music_game: asset_server.load("audio/music_game.wav"),
+            music_game_over: asset_server.load("audio/music_game_over.wav"),
+        }
+    }
+}
+
+/// Volumes from 0 to 1, music and effects are both scaled by `master`
+#[derive(Resource)]
+pub struct VolumeSettings {
+    pub master: f32,
+    pub music: f32,
+    pub sfx: f32,
+    file: storage::VersionedFile,
+}
+
+impl Default for VolumeSettings {
+    fn default() -> Self {
+        VolumeSettings {
+            master: 0.8,
+            music: 0.6,
+            sfx: 1.,
+            file: Default::default(),
+        }
+    }
+}
+
+impl VolumeSettings {
+    pub fn music_volume(&self) -> f32 {
+        self.master * self.music
+    }
+
+    pub fn sfx_volume(&self) -> f32 {
+        self.master * self.sfx
+    }
+
+    /// Reads volumes from `contents`, keeping the defaults for any lines that don't make sense.
+    /// Fails only when the file was written by a newer version of the game.
+    pub fn parse(contents: &str) -> Result<VolumeSettings, storage::Newer> {
+        let mut settings = VolumeSettings::default();
+
+        for line in storage::versioned_lines(contents, HEADER, VERSION)? {
+            let fields: Vec<&str> = line.split('\t').collect();
+
+            let volume = match fields.as_slice() {
+                ["master", _] => &mut settings.master,
+                ["music", _] => &mut settings.music,
+                ["sfx", _] => &mut settings.sfx,
+                _ => {
+                    warn!("skipping unreadable settings line {:?}", line);
+                    continue;
+                }
+            };
+
+            match fields[1].parse::<f32>() {
+                Ok(v) if v.is_finite() => *volume = v.clamp(0., 1.),
+                _ => warn!("skipping unreadable settings line {:?}", line),
+            }
+        }
+
+        Ok(settings)
+    }
+
+    pub fn serialize(&self) -> String {
+        format!(
+            "{} {}\nmaster\t{}\nmusic\t{}\nsfx\t{}\n",
+            HEADER, VERSION, self.master, self.music, self.sfx
+        )
+    }
+
+    pub fn save(&self) {
+        self.file.save(self.serialize());
+    }
+}
+
+/// The next volume up from `volume`, wrapping back round to silent after full
+pub fn step_volume(volume: f32) -> f32 {
+    // work in whole steps so repeated presses don't drift off the 10% marks
+    let steps = (volume / VOLUME_STEP).round() as u32 + 1;
+    let max_steps = (1. / VOLUME_STEP).round() as u32;
+
+    if steps > max_steps {
+        0.
+    } else {
+        steps as f32 * VOLUME_STEP
+    }
+}
+
+fn load_volume(mut commands: Commands) {
+    let (file, settings) = storage::VersionedFile::load(FILE_NAME, VolumeSettings::parse);
+
+    commands.insert_resource(VolumeSettings {
+        file,
+        ..settings.unwrap_or_default()
+    });
+}
+
+// starts a sound once it has loaded. Anything still loading or missing is skipped,
+// so a slow disk or a missing file only costs that one sound.
+fn play(
+    audio: &Audio,
+    asset_server: &AssetServer,
+    sound: &Handle<AudioSource>,
+    settings: PlaybackSettings,
+) -> Option<Handle<AudioSink>> {
+    if asset_server.get_load_state(sound) != LoadState::Loaded {
+        return None;
+    }
+
+    Some(audio.play_with_settings(sound.clone(), settings))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn play_effects(
+    audio: Res<Audio>,
+    asset_server: Res<AssetServer>,
+    sounds: Res<Sounds>,
+    volume: Res<VolumeSettings>,
+    mut dropped: EventReader<events::BombDropped>,
+    mut splashed: EventReader<events::BombSplashed>,
+    mut detonated: EventReader<events::BombDetonated>,
+    mut killed: EventReader<events::FishKilled>,
+    mut collected: EventReader<events::FishCollected>,
+    mut turtle_hit: EventReader<events::TurtleHit>,
+    mut pressed: EventReader<focus::ButtonPressed>,
+) {
+    // one of each sound a frame is plenty, a bomb killing five fish shouldn't be five times as loud
+    let effects = [
+        (dropped.iter().count(), &sounds.bomb_drop),
+        (splashed.iter().count(), &sounds.splash),
+        (detonated.iter().count(), &sounds.detonate),
+        (killed.iter().count(), &sounds.fish_kill),
+        (collected.iter().count(), &sounds.collect),
+        (turtle_hit.iter().count(), &sounds.turtle_hit),
+        (pressed.iter().count(), &sounds.click),
+    ];
+
+    let settings = PlaybackSettings::ONCE.with_volume(volume.sfx_volume());
+
+    for (_, sound) in effects.into_iter().filter(|(count, _)| *count > 0) {
+        play(&audio, &asset_server, sound, settings);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Track {
+    Menu,
+    Game,
+    GameOver,
+}
+
+impl Track {
+    // None keeps whatever is already playing, so opening settings from the pause menu
+    // doesn't switch over to the menu music
+    fn for_state(state: &AppState) -> Option<Track> {
+        match state {
+            AppState::MainMenu
+            | AppState::LevelSelect
+            | AppState::Achievements
+            | AppState::Leaderboard => Some(Track::Menu),
+            AppState::Loading | AppState::Running | AppState::Paused => Some(Track::Game),
+            AppState::GameOver => Some(Track::GameOver),
+            AppState::Settings => None,
+        }
+    }
+
+    fn sound(self, sounds: &Sounds) -> &Handle<AudioSource> {
+        match self {
+            Track::Menu => &sounds.music_menu,
+            Track::Game => &sounds.music_game,
+            Track::GameOver => &sounds.music_game_over,
+        }
+    }
+}
+
+// the track that's playing and the sink that controls it
+#[derive(Resource, Default)]
+struct MusicPlayer {
+    track: Option<Track>,
+    sink: Option<Handle<AudioSink>>,
+}
+
+fn play_music(
+    state: Res<State<AppState>>,
+    audio: Res<Audio>,
+    asset_server: Res<AssetServer>,
+    sounds: Res<Sounds>,
+    volume: Res<VolumeSettings>,
+    sinks: Res<Assets<AudioSink>>,
+    mut player: ResMut<MusicPlayer>,
+) {
+    let Some(track) = Track::for_state(&state.0) else {
+        return;
+    };
+
+    if player.track == Some(track) {
+        return;
+    }
+
+    // a track that was only just started has no sink yet, and without one it couldn't be
+    // stopped, so wait for it to turn up. With no audio device it never does, which is fine
+    // as nothing is playing anyway.
+    if player.sink.as_ref().is_some_and(|h| sinks.get(h).is_none()) {
+        return;
+    }
+
+    // keep the old track going until the new one is ready to take over
+    let settings = PlaybackSettings::LOOP.with_volume(volume.music_volume());
+    let Some(weak) = play(&audio, &asset_server, track.sound(&sounds), settings) else {
+        return;
+    };
+
+    if let Some(sink) = player.sink.take().and_then(|h| sinks.get(&h)) {
+        sink.stop();
+    }
+
+    player.track = Some(track);
+    // the sink only exists once the audio output picks the track up, and a strong handle
+    // keeps it around after that
+    player.sink = Some(sinks.get_handle(&weak));
+}
+
+fn apply_music_volume(
+    volume: Res<VolumeSettings>,
+    player: Res<MusicPlayer>,
+    sinks: Res<Assets<AudioSink>>,
+) {
+    if !volume.is_changed() {
+        return;
+    }
+
+    if let Some(sink) = player.sink.as_ref().and_then(|h| sinks.get(h)) {
+        sink.set_volume(volume.music_volume());
+    }
+}
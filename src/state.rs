@@ -3,8 +3,14 @@ use bevy::prelude::*;
 #[derive(States, PartialEq, Eq, Debug, Clone, Hash, Default)]
 pub enum AppState {
     #[default]
+    MainMenu,
+    // sets up a fresh run before switching to Running
+    Loading,
     Running,
     Paused,
-    MainMenu,
     GameOver,
+    LevelSelect,
+    Settings,
+    Achievements,
+    Leaderboard,
 }
@@ -0,0 +1,144 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use std::time::Duration;
+
+use crate::enemy;
+use crate::events;
+use crate::input;
+use crate::run::RunApp;
+use crate::score;
+use crate::sets::GameSet;
+use crate::state;
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_run_resource::<RunStats>()
+            .init_resource::<BestScore>()
+            .add_system(
+                track_run_time
+                    .in_set(GameSet::Scoring)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
+            // not tied to a state, so events from a run's last frame aren't missed
+            .add_system(record_events.in_set(GameSet::Scoring))
+            .add_system(finish_run.in_schedule(OnEnter(state::AppState::GameOver)));
+    }
+}
+
+/// Why a run came to an end
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RunEnd {
+    TurtleHit,
+    EndedEarly,
+}
+
+impl RunEnd {
+    pub fn description(&self) -> &'static str {
+        match self {
+            RunEnd::TurtleHit => "You blew up a protected turtle!",
+            RunEnd::EndedEarly => "You called it a day.",
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpeciesStats {
+    pub killed: u32,
+    pub collected: u32,
+}
+
+/// Everything that happened during the current run, shown on the results screen
+#[derive(Resource, Default)]
+pub struct RunStats {
+    pub species: HashMap<enemy::FishType, SpeciesStats>,
+    /// Fish that swam off the screen alive
+    pub escaped: u32,
+    pub bombs_used: u32,
+    /// Bombs that killed at least one fish
+    pub bombs_hit: u32,
+    /// Time spent actually playing, pauses don't count
+    pub duration: Duration,
+    pub end: Option<RunEnd>,
+    /// Set once the run is over if the score beat every earlier run
+    pub new_best: bool,
+    finished: bool,
+}
+
+impl RunStats {
+    pub fn species_mut(&mut self, fish_type: enemy::FishType) -> &mut SpeciesStats {
+        self.species.entry(fish_type).or_default()
+    }
+
+    pub fn species(&self, fish_type: enemy::FishType) -> SpeciesStats {
+        self.species.get(&fish_type).copied().unwrap_or_default()
+    }
+
+    /// Percentage of bombs dropped that killed something
+    pub fn accuracy(&self) -> f32 {
+        if self.bombs_used == 0 {
+            return 0.;
+        }
+
+        self.bombs_hit as f32 / self.bombs_used as f32 * 100.
+    }
+}
+
+/// The highest score reached in any run
+#[derive(Resource, Default)]
+pub struct BestScore {
+    pub val: u32,
+}
+
+fn track_run_time(fixed_time: Res<FixedTime>, mut stats: ResMut<RunStats>) {
+    stats.duration += fixed_time.period;
+}
+
+fn record_events(
+    mut stats: ResMut<RunStats>,
+    mut killed: EventReader<events::FishKilled>,
+    mut collected: EventReader<events::FishCollected>,
+    mut escaped: EventReader<events::FishEscaped>,
+    mut turtle_hit: EventReader<events::TurtleHit>,
+    mut dropped: EventReader<events::BombDropped>,
+) {
+    for kill in killed.iter() {
+        stats.species_mut(kill.fish_type).killed += 1;
+        if kill.first_for_bomb {
+            stats.bombs_hit += 1;
+        }
+    }
+
+    for fish in collected.iter() {
+        stats.species_mut(fish.fish_type).collected += 1;
+    }
+
+    stats.escaped += escaped.iter().count() as u32;
+    stats.bombs_used += dropped.iter().count() as u32;
+
+    if turtle_hit.iter().count() > 0 {
+        stats.end = Some(RunEnd::TurtleHit);
+    }
+}
+
+/// Wraps up the stats for the results screen once the game is over
+pub fn finish_run(
+    score: Res<score::Score>,
+    source: Res<input::InputSource>,
+    mut best: ResMut<BestScore>,
+    mut stats: ResMut<RunStats>,
+) {
+    // going to the leaderboard and back enters game over again, and the run was already
+    // wrapped up the first time
+    if stats.finished {
+        return;
+    }
+
+    stats.finished = true;
+    stats.new_best = *source == input::InputSource::Keyboard && score.val > best.val;
+
+    if stats.new_best {
+        best.val = score.val;
+    }
+}
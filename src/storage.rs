@@ -0,0 +1,151 @@
+use bevy::log::{error, warn};
+
+use std::{
+    env, fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+const APP_DIR: &str = "gonefishin";
+
+/// The folder the game keeps its saved data in, following each platform's convention.
+/// Returns `None` when there's no home folder to put it in.
+pub fn data_dir() -> Option<PathBuf> {
+    let base = if cfg!(target_os = "windows") {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+    } else {
+        env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+    };
+
+    base.map(|dir| dir.join(APP_DIR))
+}
+
+/// Full path of a file in the data folder
+pub fn data_file(name: &str) -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join(name))
+}
+
+/// Writes to a temporary file first and then swaps it into place,
+/// so a crash part way through never leaves a half written file behind.
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, path)
+}
+
+/// A file in the data folder that starts with a `<header> <version>` line.
+/// Bump a file's version whenever its line format changes.
+///
+/// Files from older versions are read as well as they can be. One from a newer version is left
+/// alone rather than overwritten, so going back to an old build never loses newer progress.
+#[derive(Default)]
+pub struct VersionedFile {
+    path: Option<PathBuf>,
+    // false when the file on disk came from a newer version of the game
+    writable: bool,
+}
+
+/// Written by a newer version of the game than this one
+#[derive(Debug)]
+pub struct Newer(pub u32);
+
+impl fmt::Display for Newer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "saved by a newer version ({})", self.0)
+    }
+}
+
+impl VersionedFile {
+    /// Reads `name` from the data folder with `parse`, if it's there yet
+    pub fn load<T>(
+        name: &str,
+        parse: impl FnOnce(&str) -> Result<T, Newer>,
+    ) -> (VersionedFile, Option<T>) {
+        let path = data_file(name);
+        let contents = path.as_ref().and_then(|p| fs::read_to_string(p).ok());
+
+        let mut file = VersionedFile {
+            path,
+            writable: true,
+        };
+
+        let value = match contents.map(|c| parse(&c)) {
+            Some(Ok(value)) => Some(value),
+            Some(Err(e)) => {
+                warn!("{} was {}, it won't be saved over", name, e);
+                file.writable = false;
+                None
+            }
+            None => None,
+        };
+
+        (file, value)
+    }
+
+    pub fn save(&self, contents: impl AsRef<[u8]>) {
+        if !self.writable {
+            return;
+        }
+
+        let Some(path) = &self.path else {
+            return;
+        };
+
+        if let Err(e) = write_atomic(path, contents) {
+            error!("couldn't save {:?}: {}", path, e);
+        }
+    }
+}
+
+/// The lines of a versioned file after its header, leaving out blank ones.
+/// An old version or a broken header still reads what it can, only a newer version fails.
+pub fn versioned_lines<'a>(
+    contents: &'a str,
+    header: &str,
+    version: u32,
+) -> Result<impl Iterator<Item = &'a str>, Newer> {
+    let mut lines = contents.lines();
+
+    match lines.next().and_then(|l| l.strip_prefix(header)) {
+        Some(found) => match found.trim().parse::<u32>() {
+            Ok(v) if v > version => return Err(Newer(v)),
+            Ok(_) => {}
+            Err(_) => warn!("{} file has a bad version, reading what we can", header),
+        },
+        None => warn!("{} file is missing its header, reading what we can", header),
+    }
+
+    Ok(lines.filter(|l| !l.trim().is_empty()))
+}
+
+/// Civil date (`YYYY-MM-DD`) of a unix timestamp in UTC
+pub fn format_date(unix_secs: u64) -> String {
+    // Howard Hinnant's days to civil algorithm
+    let days = (unix_secs / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Seconds since the unix epoch
+pub fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
@@ -0,0 +1,211 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::level;
+use crate::playfield::Playfield;
+use crate::run::{RunScoped, RunSet};
+use crate::state;
+
+pub struct TilemapPlugin;
+
+impl Plugin for TilemapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(
+            spawn_terrain
+                .in_set(RunSet::Setup)
+                .in_schedule(OnEnter(state::AppState::Loading)),
+        );
+    }
+}
+
+const TILE_SIZE: f32 = 16.;
+const SHEET_COLUMNS: usize = 22;
+const SHEET_ROWS: usize = 11;
+
+/// World position of the top left corner of every level's tile map, at the left edge of the
+/// playfield. The first row of a map sits right on top of the water line.
+fn map_origin(playfield: &Playfield) -> Vec2 {
+    Vec2::new(-playfield.width / 2., playfield.water_line() + TILE_SIZE)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TileKind {
+    Shore,
+    LakeBed,
+    Rock,
+}
+
+impl TileKind {
+    fn from_char(c: char) -> Option<TileKind> {
+        match c {
+            'g' => Some(TileKind::Shore),
+            's' => Some(TileKind::LakeBed),
+            'r' => Some(TileKind::Rock),
+            _ => None,
+        }
+    }
+
+    // top left tile of the 3x3 block in the terrain sheet used for this kind
+    fn sheet_origin(&self) -> (usize, usize) {
+        match self {
+            TileKind::Shore => (6, 0),
+            TileKind::LakeBed => (6, 4),
+            TileKind::Rock => (0, 0),
+        }
+    }
+}
+
+/// Marks the colliders built from the tile map
+#[derive(Component)]
+pub struct Terrain {}
+
+#[derive(Component)]
+struct Tile {}
+
+/// A grid of tiles parsed from a level's map.
+/// Each line of the map is a row of tiles, `g` for shore, `s` for lake bed,
+/// `r` for rock and anything else is open water.
+pub struct TileMap {
+    width: usize,
+    height: usize,
+    tiles: Vec<Option<TileKind>>,
+}
+
+impl TileMap {
+    pub fn parse(map: &str) -> TileMap {
+        let lines: Vec<&str> = map.lines().collect();
+        let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+        let height = lines.len();
+
+        let mut tiles = vec![None; width * height];
+        for (row, line) in lines.iter().enumerate() {
+            for (col, c) in line.chars().enumerate() {
+                tiles[row * width + col] = TileKind::from_char(c);
+            }
+        }
+
+        TileMap {
+            width,
+            height,
+            tiles,
+        }
+    }
+
+    pub fn get(&self, col: isize, row: isize) -> Option<TileKind> {
+        if col < 0 || row < 0 || col as usize >= self.width || row as usize >= self.height {
+            return None;
+        }
+
+        self.tiles[row as usize * self.width + col as usize]
+    }
+
+    /// Picks the sprite for a tile by looking at which of its neighbors are the same kind,
+    /// so the edges of every patch of terrain get a border.
+    pub fn sheet_index(&self, col: usize, row: usize) -> Option<usize> {
+        let kind = self.get(col as isize, row as isize)?;
+        let same =
+            |dc: isize, dr: isize| self.get(col as isize + dc, row as isize + dr) == Some(kind);
+
+        let sheet_row = match (same(0, -1), same(0, 1)) {
+            (false, _) => 0,
+            (true, false) => 2,
+            (true, true) => 1,
+        };
+
+        let sheet_col = match (same(-1, 0), same(1, 0)) {
+            (false, true) => 0,
+            (true, false) => 2,
+            _ => 1,
+        };
+
+        let (origin_col, origin_row) = kind.sheet_origin();
+        Some((origin_row + sheet_row) * SHEET_COLUMNS + origin_col + sheet_col)
+    }
+
+    /// Runs of solid tiles in each row as `(row, first_col, last_col)`.
+    /// One collider per run keeps the physics world small.
+    pub fn solid_runs(&self) -> Vec<(usize, usize, usize)> {
+        let mut runs = Vec::new();
+
+        for row in 0..self.height {
+            let mut start = None;
+
+            for col in 0..=self.width {
+                let solid = col < self.width && self.tiles[row * self.width + col].is_some();
+
+                match (solid, start) {
+                    (true, None) => start = Some(col),
+                    (false, Some(first)) => {
+                        runs.push((row, first, col - 1));
+                        start = None;
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        runs
+    }
+}
+
+fn tile_center(origin: Vec2, col: f32, row: f32) -> Vec2 {
+    origin
+        + Vec2::new(
+            col * TILE_SIZE + TILE_SIZE / 2.,
+            -(row * TILE_SIZE + TILE_SIZE / 2.),
+        )
+}
+
+fn spawn_terrain(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    selected: Res<level::SelectedLevel>,
+    playfield: Res<Playfield>,
+) {
+    let origin = map_origin(&playfield);
+    let texture_handle = asset_server.load("Free/Terrain/Terrain (16x16).png");
+    let texture_atlas = TextureAtlas::from_grid(
+        texture_handle,
+        Vec2::new(TILE_SIZE, TILE_SIZE),
+        SHEET_COLUMNS,
+        SHEET_ROWS,
+        None,
+        None,
+    );
+    let texture_atlas_handle = texture_atlases.add(texture_atlas);
+
+    let map = TileMap::parse(selected.level().terrain);
+
+    for row in 0..map.height {
+        for col in 0..map.width {
+            if let Some(index) = map.sheet_index(col, row) {
+                commands.spawn((
+                    Tile {},
+                    RunScoped {},
+                    SpriteSheetBundle {
+                        texture_atlas: texture_atlas_handle.clone(),
+                        sprite: TextureAtlasSprite::new(index),
+                        transform: Transform::from_translation(
+                            tile_center(origin, col as f32, row as f32).extend(0.2),
+                        ),
+                        ..default()
+                    },
+                ));
+            }
+        }
+    }
+
+    for (row, first, last) in map.solid_runs() {
+        let tiles = (last - first + 1) as f32;
+        let center = tile_center(origin, (first + last) as f32 / 2., row as f32);
+
+        commands.spawn((
+            Terrain {},
+            RunScoped {},
+            RigidBody::Fixed,
+            Collider::cuboid(tiles * TILE_SIZE / 2., TILE_SIZE / 2.),
+            TransformBundle::from(Transform::from_translation(center.extend(0.2))),
+        ));
+    }
+}
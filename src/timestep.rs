@@ -0,0 +1,136 @@
+use bevy::{prelude::*, transform::TransformSystem};
+use bevy_rapier2d::prelude::*;
+
+use std::time::Duration;
+
+use crate::sets::GameSet;
+use crate::state::AppState;
+
+/// Runs gameplay and physics in fixed ticks, so a run plays out the same however fast the
+/// game is drawn. Anything that moves a run along goes in one of the tick's `GameSet`s in
+/// `CoreSchedule::FixedUpdate`, and moves by `FixedTime::period` rather than the frame's delta.
+pub struct TimestepPlugin;
+
+impl Plugin for TimestepPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(FixedTime::new(TICK))
+            // rapier steps by exactly a tick, rather than by however long the frame took
+            .insert_resource(RapierConfiguration {
+                timestep_mode: TimestepMode::Fixed {
+                    dt: TICK.as_secs_f32(),
+                    substeps: 1,
+                },
+                ..default()
+            })
+            .add_plugin(
+                RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.)
+                    .with_default_system_setup(false),
+            )
+            .edit_schedule(CoreSchedule::FixedUpdate, |schedule| {
+                schedule
+                    .configure_sets(
+                        (
+                            GameSet::Input,
+                            GameSet::Movement,
+                            GameSet::Combat,
+                            GameSet::Scoring,
+                        )
+                            .chain(),
+                    )
+                    .configure_sets(
+                        (
+                            PhysicsSet::SyncBackend,
+                            PhysicsSet::SyncBackendFlush,
+                            PhysicsSet::StepSimulation,
+                            PhysicsSet::Writeback,
+                        )
+                            .chain(),
+                    )
+                    // rapier catches up with where everything moved to and steps in between
+                    .configure_set(GameSet::Movement.before(PhysicsSet::SyncBackend))
+                    .configure_set(GameSet::Combat.after(PhysicsSet::Writeback))
+                    // physics keeps syncing outside a run, so despawned colliders are cleaned up
+                    .configure_set(GameSet::Input.run_if(in_state(AppState::Running)))
+                    .configure_set(GameSet::Movement.run_if(in_state(AppState::Running)))
+                    .configure_set(GameSet::Combat.run_if(in_state(AppState::Running)))
+                    .configure_set(GameSet::Scoring.run_if(in_state(AppState::Running)))
+                    .add_systems((
+                        remember_positions.before(GameSet::Input),
+                        // whatever moved in or spawned this tick is in place before physics sees it
+                        apply_system_buffers
+                            .after(GameSet::Movement)
+                            .before(PhysicsSet::SyncBackend),
+                    ))
+                    .add_systems(
+                        RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsSet::SyncBackend)
+                            .in_base_set(PhysicsSet::SyncBackend),
+                    )
+                    .add_systems(
+                        RapierPhysicsPlugin::<NoUserData>::get_systems(
+                            PhysicsSet::SyncBackendFlush,
+                        )
+                        .in_base_set(PhysicsSet::SyncBackendFlush),
+                    )
+                    .add_systems(
+                        RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsSet::StepSimulation)
+                            .in_base_set(PhysicsSet::StepSimulation),
+                    )
+                    .add_systems(
+                        RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsSet::Writeback)
+                            .in_base_set(PhysicsSet::Writeback),
+                    );
+            })
+            // a frame reacts to everything its ticks did before drawing any of it
+            .configure_set(GameSet::Scoring.before(GameSet::Ui))
+            .add_systems(
+                (
+                    interpolate
+                        .in_base_set(CoreSet::PostUpdate)
+                        .before(TransformSystem::TransformPropagate),
+                    restore_positions
+                        .in_base_set(CoreSet::PostUpdate)
+                        .after(TransformSystem::TransformPropagate),
+                )
+                    .distributive_run_if(in_state(AppState::Running)),
+            );
+    }
+}
+
+/// How much game time each tick covers
+pub const TICK: Duration = Duration::from_nanos(16_666_667);
+
+/// Drawn part way between where it was at the last two ticks, so it moves smoothly
+/// even when the screen refreshes faster or slower than the game ticks
+#[derive(Component, Default)]
+pub struct Interpolated {
+    // where it was before the latest tick, none until it has been through one
+    previous: Option<Vec3>,
+    // where the simulation has it, put back once it has been drawn
+    current: Vec3,
+}
+
+fn remember_positions(mut q: Query<(&Transform, &mut Interpolated)>) {
+    for (transform, mut interpolated) in q.iter_mut() {
+        interpolated.previous = Some(transform.translation);
+    }
+}
+
+fn interpolate(fixed_time: Res<FixedTime>, mut q: Query<(&mut Transform, &mut Interpolated)>) {
+    // how far through the next tick the clock has got
+    let alpha = (fixed_time.accumulated().as_secs_f32() / fixed_time.period.as_secs_f32()).min(1.);
+
+    for (mut transform, mut interpolated) in q.iter_mut() {
+        interpolated.current = transform.translation;
+
+        if let Some(previous) = interpolated.previous {
+            transform.translation = previous.lerp(interpolated.current, alpha);
+        }
+    }
+}
+
+// ticks only ever see where things really are
+fn restore_positions(mut q: Query<(&mut Transform, &Interpolated)>) {
+    for (mut transform, interpolated) in q.iter_mut() {
+        transform.translation = interpolated.current;
+    }
+}
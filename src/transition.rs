@@ -0,0 +1,284 @@
+use bevy::prelude::*;
+
+use std::time::Duration;
+
+use crate::state::AppState;
+use crate::ui::focus;
+
+pub struct TransitionPlugin;
+
+impl Plugin for TransitionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ScreenTransition>()
+            .insert_resource(Transitions::default())
+            .add_startup_system(spawn_overlay)
+            .add_system(
+                drive_transition
+                    .in_base_set(CoreSet::StateTransitions)
+                    .before(apply_state_transition::<AppState>),
+            )
+            .add_system(draw_transition)
+            // menus can't be clicked through a transition
+            .configure_set(focus::FocusSet.run_if(idle));
+    }
+}
+
+// how many diamonds across and down the wipe is made of
+const WIPE_COLUMNS: usize = 16;
+const WIPE_ROWS: usize = 9;
+// how far behind the leftmost column the rightmost one starts growing
+const WIPE_SWEEP: f32 = 0.5;
+
+const COVER_COLOR: Color = Color::rgb(0.13, 0.12, 0.19);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TransitionStyle {
+    /// Switch straight away, the way states change without transitions
+    Cut,
+    Fade,
+    /// Diamonds from `Transition.png` grow across the screen
+    Wipe,
+}
+
+/// Which transition plays between two states.
+/// Rules are checked in order and `None` matches any state.
+#[derive(Resource)]
+pub struct Transitions {
+    rules: Vec<(Option<AppState>, Option<AppState>, TransitionStyle)>,
+    pub default: TransitionStyle,
+    /// How long covering, and then uncovering, the screen each take
+    pub duration: Duration,
+}
+
+impl Transitions {
+    pub fn with(
+        mut self,
+        from: Option<AppState>,
+        to: Option<AppState>,
+        style: TransitionStyle,
+    ) -> Self {
+        self.rules.push((from, to, style));
+        self
+    }
+
+    pub fn style(&self, from: &AppState, to: &AppState) -> TransitionStyle {
+        self.rules
+            .iter()
+            .find(|(rule_from, rule_to, _)| {
+                rule_from.as_ref().is_none_or(|s| s == from)
+                    && rule_to.as_ref().is_none_or(|s| s == to)
+            })
+            .map(|(_, _, style)| *style)
+            .unwrap_or(self.default)
+    }
+}
+
+impl Default for Transitions {
+    fn default() -> Self {
+        Transitions {
+            rules: vec![],
+            default: TransitionStyle::Fade,
+            duration: Duration::from_millis(300),
+        }
+        // pausing has to feel instant
+        .with(
+            Some(AppState::Running),
+            Some(AppState::Paused),
+            TransitionStyle::Cut,
+        )
+        .with(
+            Some(AppState::Paused),
+            Some(AppState::Running),
+            TransitionStyle::Cut,
+        )
+        // the run has to stop the moment it's lost, or fish keep scoring under the fade
+        .with(
+            Some(AppState::Running),
+            Some(AppState::GameOver),
+            TransitionStyle::Cut,
+        )
+        // loading hands over to the run while the screen is still covered
+        .with(
+            Some(AppState::Loading),
+            Some(AppState::Running),
+            TransitionStyle::Cut,
+        )
+        .with(None, Some(AppState::Loading), TransitionStyle::Wipe)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+enum Phase {
+    #[default]
+    Idle,
+    /// Covering the old state
+    Out,
+    /// Uncovering the new one
+    In,
+}
+
+/// The transition that is playing, if any
+#[derive(Resource)]
+pub struct ScreenTransition {
+    phase: Phase,
+    style: TransitionStyle,
+    target: Option<AppState>,
+    /// How much of the screen is covered, from 0 to 1
+    coverage: f32,
+}
+
+impl Default for ScreenTransition {
+    fn default() -> Self {
+        ScreenTransition {
+            phase: Phase::Idle,
+            style: TransitionStyle::Cut,
+            target: None,
+            coverage: 0.,
+        }
+    }
+}
+
+/// Run condition for anything that takes input or spawns things,
+/// which should wait until transitions are over
+pub fn idle(transition: Res<ScreenTransition>) -> bool {
+    transition.phase == Phase::Idle
+}
+
+// holds back state changes until the screen is covered, then lets them through
+fn drive_transition(
+    time: Res<Time>,
+    transitions: Res<Transitions>,
+    state: Res<State<AppState>>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut transition: ResMut<ScreenTransition>,
+) {
+    if let Some(target) = next_state.0.take() {
+        let style = transitions.style(&state.0, &target);
+
+        if style == TransitionStyle::Cut {
+            next_state.0 = Some(target);
+        } else {
+            transition.phase = Phase::Out;
+            transition.style = style;
+            transition.target = Some(target);
+        }
+    }
+
+    // real time, so transitions still play while the game clock is paused
+    let step = time.raw_delta_seconds() / transitions.duration.as_secs_f32();
+
+    match transition.phase {
+        Phase::Idle => {}
+        Phase::Out => {
+            transition.coverage = (transition.coverage + step).min(1.);
+            if transition.coverage >= 1. {
+                next_state.0 = transition.target.take();
+                transition.phase = Phase::In;
+            }
+        }
+        Phase::In => {
+            transition.coverage = (transition.coverage - step).max(0.);
+            if transition.coverage <= 0. {
+                transition.phase = Phase::Idle;
+            }
+        }
+    }
+}
+
+#[derive(Component)]
+struct TransitionOverlay {}
+
+#[derive(Component)]
+struct WipeDiamond {
+    column: usize,
+    row: usize,
+}
+
+fn spawn_overlay(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let diamond = asset_server.load("Free/Other/Transition.png");
+
+    commands
+        .spawn((
+            TransitionOverlay {},
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    position_type: PositionType::Absolute,
+                    ..default()
+                },
+                background_color: Color::NONE.into(),
+                visibility: Visibility::Hidden,
+                z_index: ZIndex::Global(i32::MAX),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            for row in 0..WIPE_ROWS {
+                for column in 0..WIPE_COLUMNS {
+                    parent.spawn((
+                        WipeDiamond { column, row },
+                        ImageBundle {
+                            style: Style {
+                                position_type: PositionType::Absolute,
+                                ..default()
+                            },
+                            image: UiImage::new(diamond.clone()),
+                            ..default()
+                        },
+                    ));
+                }
+            }
+        });
+}
+
+fn draw_transition(
+    transition: Res<ScreenTransition>,
+    mut overlay_q: Query<(&mut Visibility, &mut BackgroundColor), With<TransitionOverlay>>,
+    mut diamond_q: Query<(&WipeDiamond, &mut Style, &mut Visibility), Without<TransitionOverlay>>,
+) {
+    if !transition.is_changed() {
+        return;
+    }
+
+    let Ok((mut visibility, mut background)) = overlay_q.get_single_mut() else {
+        return;
+    };
+
+    *visibility = if transition.phase == Phase::Idle {
+        Visibility::Hidden
+    } else {
+        Visibility::Inherited
+    };
+
+    let fading = transition.style == TransitionStyle::Fade;
+    background.0 = if fading {
+        COVER_COLOR.with_a(transition.coverage)
+    } else {
+        Color::NONE
+    };
+
+    for (diamond, mut style, mut visibility) in diamond_q.iter_mut() {
+        *visibility = if fading {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+
+        // columns on the left start first so the wipe sweeps across
+        let delay = diamond.column as f32 / (WIPE_COLUMNS - 1) as f32 * WIPE_SWEEP;
+        let grown = (transition.coverage * (1. + WIPE_SWEEP) - delay).clamp(0., 1.);
+
+        // a diamond has to be twice the size of its cell to cover it
+        let width = grown * 200. / WIPE_COLUMNS as f32;
+        let height = grown * 200. / WIPE_ROWS as f32;
+        let center_x = (diamond.column as f32 + 0.5) * 100. / WIPE_COLUMNS as f32;
+        let center_y = (diamond.row as f32 + 0.5) * 100. / WIPE_ROWS as f32;
+
+        style.size = Size::new(Val::Percent(width), Val::Percent(height));
+        style.position = UiRect {
+            left: Val::Percent(center_x - width / 2.),
+            top: Val::Percent(center_y - height / 2.),
+            ..default()
+        };
+    }
+}
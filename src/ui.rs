@@ -0,0 +1,17 @@
+use bevy::prelude::*;
+
+pub mod bitmap_text;
+pub mod focus;
+pub mod theme;
+pub mod widgets;
+
+pub struct UiPlugin;
+
+impl Plugin for UiPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<theme::Theme>()
+            .add_plugin(bitmap_text::BitmapTextPlugin)
+            .add_plugin(focus::FocusPlugin)
+            .add_systems((theme::render_text, theme::restyle_surfaces));
+    }
+}
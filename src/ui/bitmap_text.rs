@@ -0,0 +1,175 @@
+use bevy::{prelude::*, render::texture::ImageSampler};
+
+pub struct BitmapTextPlugin;
+
+impl Plugin for BitmapTextPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BitmapFont>()
+            .add_system(use_nearest_sampling)
+            .add_system(layout_bitmap_text);
+    }
+}
+
+const GLYPH_WIDTH: f32 = 8.;
+const GLYPH_HEIGHT: f32 = 10.;
+const SHEET_COLUMNS: usize = 10;
+const SHEET_ROWS: usize = 5;
+
+// the sheet has the letters, then the digits, then punctuation, ten to a row
+const GLYPHS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ    0123456789.,:?!()+-";
+
+/// The pixel art font from the menu art, drawn in white so it can be tinted any color
+#[derive(Resource)]
+pub struct BitmapFont {
+    sheet: Handle<Image>,
+}
+
+impl FromWorld for BitmapFont {
+    fn from_world(world: &mut World) -> Self {
+        BitmapFont {
+            sheet: world
+                .resource::<AssetServer>()
+                .load("Free/Menu/Text/Text (White) (8x10).png"),
+        }
+    }
+}
+
+/// Text drawn with the bitmap font, one glyph per node.
+/// Letters are shown in upper case and anything the font is missing is left blank.
+#[derive(Component, Clone)]
+pub struct BitmapText {
+    pub value: String,
+    /// Whole number multiple of the 8x10 glyph size, so pixels stay square
+    pub scale: u32,
+    pub color: Color,
+    /// Lines are lined up against each other when the text has more than one
+    pub alignment: TextAlignment,
+}
+
+impl BitmapText {
+    pub fn new(value: impl Into<String>, scale: u32, color: Color) -> Self {
+        BitmapText {
+            value: value.into(),
+            scale,
+            color,
+            alignment: TextAlignment::Left,
+        }
+    }
+
+    pub fn with_alignment(mut self, alignment: TextAlignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+}
+
+#[derive(Bundle)]
+pub struct BitmapTextBundle {
+    pub text: BitmapText,
+    pub node: NodeBundle,
+}
+
+impl BitmapTextBundle {
+    pub fn new(text: BitmapText) -> Self {
+        BitmapTextBundle {
+            text,
+            node: NodeBundle::default(),
+        }
+    }
+}
+
+/// Row and column of a character in the sheet, `None` for blanks
+fn glyph_position(c: char) -> Option<(usize, usize)> {
+    if c == ' ' {
+        return None;
+    }
+
+    let c = c.to_ascii_uppercase();
+    GLYPHS
+        .chars()
+        .position(|g| g == c)
+        .map(|i| (i / SHEET_COLUMNS, i % SHEET_COLUMNS))
+}
+
+// the glyphs are scaled up a lot, smoothing would blur them
+fn use_nearest_sampling(
+    font: Res<BitmapFont>,
+    mut events: EventReader<AssetEvent<Image>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    for event in events.iter() {
+        if let AssetEvent::Created { handle } = event {
+            if *handle == font.sheet {
+                if let Some(image) = images.get_mut(handle) {
+                    image.sampler_descriptor = ImageSampler::nearest();
+                }
+            }
+        }
+    }
+}
+
+fn spawn_glyph(parent: &mut ChildBuilder, font: &BitmapFont, text: &BitmapText, c: char) {
+    let scale = text.scale.max(1) as f32;
+
+    // each glyph is a window onto the whole sheet, shifted so only its cell shows
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Px(GLYPH_WIDTH * scale), Val::Px(GLYPH_HEIGHT * scale)),
+                overflow: Overflow::Hidden,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            let Some((row, column)) = glyph_position(c) else {
+                return;
+            };
+
+            parent.spawn(ImageBundle {
+                style: Style {
+                    size: Size::new(
+                        Val::Px(GLYPH_WIDTH * SHEET_COLUMNS as f32 * scale),
+                        Val::Px(GLYPH_HEIGHT * SHEET_ROWS as f32 * scale),
+                    ),
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        left: Val::Px(-(column as f32) * GLYPH_WIDTH * scale),
+                        top: Val::Px(-(row as f32) * GLYPH_HEIGHT * scale),
+                        ..default()
+                    },
+                    ..default()
+                },
+                image: UiImage::new(font.sheet.clone()),
+                background_color: text.color.into(),
+                ..default()
+            });
+        });
+}
+
+fn layout_bitmap_text(
+    mut commands: Commands,
+    font: Res<BitmapFont>,
+    mut text_q: Query<(Entity, &BitmapText, &mut Style), Changed<BitmapText>>,
+) {
+    for (entity, text, mut style) in text_q.iter_mut() {
+        style.flex_direction = FlexDirection::Column;
+        style.gap = Size::height(Val::Px(text.scale as f32));
+        style.align_items = match text.alignment {
+            TextAlignment::Left => AlignItems::FlexStart,
+            TextAlignment::Center => AlignItems::Center,
+            TextAlignment::Right => AlignItems::FlexEnd,
+        };
+
+        let mut text_commands = commands.entity(entity);
+        text_commands.despawn_descendants();
+        text_commands.with_children(|parent| {
+            for line in text.value.lines() {
+                parent.spawn(NodeBundle::default()).with_children(|parent| {
+                    for c in line.chars() {
+                        spawn_glyph(parent, &font, text, c);
+                    }
+                });
+            }
+        });
+    }
+}
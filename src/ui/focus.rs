@@ -0,0 +1,198 @@
+use bevy::prelude::*;
+
+use super::theme::Theme;
+
+pub struct FocusPlugin;
+
+impl Plugin for FocusPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<UiFocus>()
+            .add_event::<ButtonPressed>()
+            .add_systems(
+                (
+                    keep_focus_valid,
+                    mouse_focus,
+                    navigate_focus,
+                    confirm_focus,
+                    highlight_focus,
+                )
+                    .chain()
+                    .in_set(FocusSet),
+            );
+    }
+}
+
+/// Runs the focus systems, anything reacting to `ButtonPressed` should run after it
+#[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone)]
+pub struct FocusSet;
+
+/// The button keyboard and gamepad input act on.
+/// Every `Button` on screen can take focus, in reading order.
+#[derive(Resource, Default)]
+pub struct UiFocus {
+    pub entity: Option<Entity>,
+    /// Stops keys from moving focus or pressing buttons, e.g. while typing
+    pub locked: bool,
+}
+
+/// Sent when a button is clicked, or confirmed while it has focus
+pub struct ButtonPressed {
+    pub entity: Entity,
+}
+
+pub fn gamepad_just_pressed(
+    gamepads: &Gamepads,
+    buttons: &Input<GamepadButton>,
+    button_types: &[GamepadButtonType],
+) -> bool {
+    gamepads.iter().any(|gamepad| {
+        button_types
+            .iter()
+            .any(|&button_type| buttons.just_pressed(GamepadButton::new(gamepad, button_type)))
+    })
+}
+
+// buttons that have been laid out and are showing, top to bottom then left to right
+fn focus_order(
+    button_q: &Query<(Entity, &Node, &GlobalTransform, &ComputedVisibility), With<Button>>,
+) -> Vec<Entity> {
+    let mut buttons: Vec<(Entity, Vec3)> = button_q
+        .iter()
+        .filter(|(_, node, _, visibility)| node.size() != Vec2::ZERO && visibility.is_visible())
+        .map(|(e, _, transform, _)| (e, transform.translation()))
+        .collect();
+
+    buttons.sort_by(|(_, a), (_, b)| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)));
+    buttons.into_iter().map(|(e, _)| e).collect()
+}
+
+// focus falls back to the first button whenever the focused one goes away
+fn keep_focus_valid(
+    mut focus: ResMut<UiFocus>,
+    button_q: Query<(Entity, &Node, &GlobalTransform, &ComputedVisibility), With<Button>>,
+) {
+    let order = focus_order(&button_q);
+
+    match focus.entity {
+        Some(e) if order.contains(&e) => {}
+        _ => {
+            let first = order.first().copied();
+            if focus.entity != first {
+                focus.entity = first;
+            }
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn mouse_focus(
+    mut focus: ResMut<UiFocus>,
+    button_q: Query<(Entity, &Interaction), (Changed<Interaction>, With<Button>)>,
+    mut pressed: EventWriter<ButtonPressed>,
+) {
+    for (entity, interaction) in button_q.iter() {
+        match *interaction {
+            Interaction::Clicked => {
+                focus.entity = Some(entity);
+                pressed.send(ButtonPressed { entity });
+            }
+            Interaction::Hovered => {
+                focus.entity = Some(entity);
+            }
+            Interaction::None => {}
+        }
+    }
+}
+
+fn navigate_focus(
+    keys: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+    mut focus: ResMut<UiFocus>,
+    button_q: Query<(Entity, &Node, &GlobalTransform, &ComputedVisibility), With<Button>>,
+) {
+    if focus.locked {
+        return;
+    }
+
+    let shift = keys.any_pressed([KeyCode::LShift, KeyCode::RShift]);
+    let tab = keys.just_pressed(KeyCode::Tab);
+
+    let next = (tab && !shift)
+        || keys.any_just_pressed([KeyCode::Down, KeyCode::Right])
+        || gamepad_just_pressed(
+            &gamepads,
+            &buttons,
+            &[GamepadButtonType::DPadDown, GamepadButtonType::DPadRight],
+        );
+
+    let prev = (tab && shift)
+        || keys.any_just_pressed([KeyCode::Up, KeyCode::Left])
+        || gamepad_just_pressed(
+            &gamepads,
+            &buttons,
+            &[GamepadButtonType::DPadUp, GamepadButtonType::DPadLeft],
+        );
+
+    if !next && !prev {
+        return;
+    }
+
+    let order = focus_order(&button_q);
+    if order.is_empty() {
+        return;
+    }
+
+    let current = focus
+        .entity
+        .and_then(|e| order.iter().position(|&o| o == e))
+        .unwrap_or(0);
+
+    let index = if next {
+        (current + 1) % order.len()
+    } else {
+        (current + order.len() - 1) % order.len()
+    };
+
+    focus.entity = Some(order[index]);
+}
+
+fn confirm_focus(
+    keys: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+    focus: Res<UiFocus>,
+    mut pressed: EventWriter<ButtonPressed>,
+) {
+    if focus.locked {
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::Return)
+        || gamepad_just_pressed(&gamepads, &buttons, &[GamepadButtonType::South])
+    {
+        if let Some(entity) = focus.entity {
+            pressed.send(ButtonPressed { entity });
+        }
+    }
+}
+
+// the mouse wins over focus so it's clear what a click would do
+fn highlight_focus(
+    theme: Res<Theme>,
+    focus: Res<UiFocus>,
+    mut button_q: Query<(Entity, &Interaction, &mut BackgroundColor), With<Button>>,
+) {
+    for (entity, interaction, mut color) in button_q.iter_mut() {
+        let target = match *interaction {
+            Interaction::Clicked => theme.button_pressed,
+            Interaction::Hovered => theme.button_hovered,
+            Interaction::None if Some(entity) == focus.entity => theme.button_focused,
+            Interaction::None => theme.button,
+        };
+
+        if color.0 != target {
+            color.0 = target;
+        }
+    }
+}
@@ -0,0 +1,244 @@
+use bevy::prelude::*;
+
+use super::bitmap_text::{BitmapText, BitmapTextBundle};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ThemeKind {
+    Light,
+    Dark,
+}
+
+impl ThemeKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ThemeKind::Light => "Light",
+            ThemeKind::Dark => "Dark",
+        }
+    }
+
+    pub fn next(&self) -> ThemeKind {
+        match self {
+            ThemeKind::Light => ThemeKind::Dark,
+            ThemeKind::Dark => ThemeKind::Light,
+        }
+    }
+}
+
+/// Which font text is drawn in
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FontFace {
+    Smooth,
+    /// The bitmap font from the menu art
+    Pixel,
+}
+
+impl FontFace {
+    pub fn name(&self) -> &'static str {
+        match self {
+            FontFace::Smooth => "Smooth",
+            FontFace::Pixel => "Pixel",
+        }
+    }
+
+    pub fn next(&self) -> FontFace {
+        match self {
+            FontFace::Smooth => FontFace::Pixel,
+            FontFace::Pixel => FontFace::Smooth,
+        }
+    }
+}
+
+/// What a piece of text is for, which decides how big it's drawn
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TextRole {
+    Title,
+    Heading,
+    Body,
+    Small,
+}
+
+/// Fonts, sizes and colors shared by every screen.
+/// Changing it restyles everything already on screen.
+#[derive(Resource, Clone)]
+pub struct Theme {
+    pub kind: ThemeKind,
+    pub face: FontFace,
+    pub font: Handle<Font>,
+    pub title_size: f32,
+    pub heading_size: f32,
+    pub body_size: f32,
+    pub small_size: f32,
+    pub text: Color,
+    pub highlight_text: Color,
+    /// Behind every full screen menu
+    pub backdrop: Color,
+    /// Behind menus shown on top of a run, like the pause menu
+    pub overlay: Color,
+    pub panel: Color,
+    pub button: Color,
+    pub button_hovered: Color,
+    pub button_pressed: Color,
+    pub button_focused: Color,
+}
+
+impl Theme {
+    pub fn new(kind: ThemeKind, face: FontFace, font: Handle<Font>) -> Self {
+        let sizes = Theme {
+            kind,
+            face,
+            font,
+            title_size: 50.,
+            heading_size: 35.,
+            body_size: 25.,
+            small_size: 20.,
+            text: Color::BLACK,
+            highlight_text: Color::DARK_GREEN,
+            backdrop: Color::NONE,
+            overlay: Color::rgba(1., 1., 1., 0.5),
+            panel: Color::rgba(1., 1., 1., 0.5),
+            button: Color::WHITE,
+            button_hovered: Color::BISQUE,
+            button_pressed: Color::ORANGE,
+            button_focused: Color::LIME_GREEN,
+        };
+
+        match kind {
+            ThemeKind::Light => sizes,
+            ThemeKind::Dark => Theme {
+                text: Color::WHITE,
+                highlight_text: Color::LIME_GREEN,
+                backdrop: Color::rgba(0.05, 0.08, 0.12, 0.85),
+                overlay: Color::rgba(0., 0., 0., 0.6),
+                panel: Color::rgba(0.15, 0.18, 0.25, 0.9),
+                button: Color::rgb(0.2, 0.22, 0.3),
+                button_hovered: Color::rgb(0.3, 0.33, 0.45),
+                button_pressed: Color::rgb(0.6, 0.35, 0.1),
+                button_focused: Color::rgb(0.1, 0.5, 0.25),
+                ..sizes
+            },
+        }
+    }
+
+    pub fn font_size(&self, role: TextRole) -> f32 {
+        match role {
+            TextRole::Title => self.title_size,
+            TextRole::Heading => self.heading_size,
+            TextRole::Body => self.body_size,
+            TextRole::Small => self.small_size,
+        }
+    }
+
+    /// How many times bigger than the 8x10 glyphs the pixel font is drawn
+    pub fn pixel_scale(&self, role: TextRole) -> u32 {
+        match role {
+            TextRole::Title => 4,
+            TextRole::Heading => 3,
+            TextRole::Body | TextRole::Small => 2,
+        }
+    }
+}
+
+impl FromWorld for Theme {
+    fn from_world(world: &mut World) -> Self {
+        let font = world.resource::<AssetServer>().load("fonts/OpenSans.ttf");
+        Theme::new(ThemeKind::Light, FontFace::Smooth, font)
+    }
+}
+
+/// Text that follows the theme, drawn in whichever font face it picks.
+/// Change `value` to change what it says.
+#[derive(Component)]
+pub struct UiText {
+    pub value: String,
+    pub role: TextRole,
+    /// Drawn in the theme's highlight color instead of the text color
+    pub highlight: bool,
+}
+
+impl UiText {
+    pub fn new(role: TextRole, value: impl Into<String>) -> Self {
+        UiText {
+            value: value.into(),
+            role,
+            highlight: false,
+        }
+    }
+
+    pub fn highlighted(role: TextRole, value: impl Into<String>) -> Self {
+        UiText {
+            highlight: true,
+            ..UiText::new(role, value)
+        }
+    }
+
+    fn color(&self, theme: &Theme) -> Color {
+        if self.highlight {
+            theme.highlight_text
+        } else {
+            theme.text
+        }
+    }
+}
+
+/// A node whose background comes from the theme
+#[derive(Component, Clone, Copy)]
+pub enum Surface {
+    Backdrop,
+    Overlay,
+    Panel,
+}
+
+impl Surface {
+    pub fn color(&self, theme: &Theme) -> Color {
+        match self {
+            Surface::Backdrop => theme.backdrop,
+            Surface::Overlay => theme.overlay,
+            Surface::Panel => theme.panel,
+        }
+    }
+}
+
+// the text itself lives in a child, so switching font face is just a matter of replacing it
+pub fn render_text(
+    mut commands: Commands,
+    theme: Res<Theme>,
+    text_q: Query<(Entity, Ref<UiText>)>,
+) {
+    for (entity, ui_text) in text_q.iter() {
+        if !theme.is_changed() && !ui_text.is_changed() {
+            continue;
+        }
+
+        let mut text_commands = commands.entity(entity);
+        text_commands.despawn_descendants();
+        text_commands.with_children(|parent| match theme.face {
+            FontFace::Smooth => {
+                parent.spawn(TextBundle::from_section(
+                    ui_text.value.clone(),
+                    TextStyle {
+                        font: theme.font.clone(),
+                        font_size: theme.font_size(ui_text.role),
+                        color: ui_text.color(&theme),
+                    },
+                ));
+            }
+            FontFace::Pixel => {
+                parent.spawn(BitmapTextBundle::new(BitmapText::new(
+                    ui_text.value.clone(),
+                    theme.pixel_scale(ui_text.role),
+                    ui_text.color(&theme),
+                )));
+            }
+        });
+    }
+}
+
+pub fn restyle_surfaces(theme: Res<Theme>, mut surface_q: Query<(&Surface, &mut BackgroundColor)>) {
+    if !theme.is_changed() {
+        return;
+    }
+
+    for (surface, mut color) in surface_q.iter_mut() {
+        color.0 = surface.color(&theme);
+    }
+}
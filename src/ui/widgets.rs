@@ -0,0 +1,207 @@
+use bevy::prelude::*;
+
+use super::theme::{Surface, TextRole, Theme, UiText};
+
+fn root(commands: &mut Commands, theme: &Theme, surface: Surface, marker: impl Bundle) -> Entity {
+    commands
+        .spawn((
+            marker,
+            surface,
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    gap: Size::height(Val::Px(10.)),
+                    ..default()
+                },
+                background_color: surface.color(theme).into(),
+                ..default()
+            },
+        ))
+        .id()
+}
+
+/// Full screen column that a menu is laid out in, `marker` is what it gets despawned by
+pub fn screen(commands: &mut Commands, theme: &Theme, marker: impl Bundle) -> Entity {
+    root(commands, theme, Surface::Backdrop, marker)
+}
+
+/// Like `screen` but dims whatever is underneath, for menus on top of a run
+pub fn overlay(commands: &mut Commands, theme: &Theme, marker: impl Bundle) -> Entity {
+    root(commands, theme, Surface::Overlay, marker)
+}
+
+/// Themed text, for spawning alongside other components
+pub fn text_bundle(ui_text: UiText) -> (UiText, NodeBundle) {
+    (ui_text, NodeBundle::default())
+}
+
+pub fn text(parent: &mut ChildBuilder, role: TextRole, value: impl Into<String>) -> Entity {
+    parent.spawn(text_bundle(UiText::new(role, value))).id()
+}
+
+pub fn title(parent: &mut ChildBuilder, value: impl Into<String>) -> Entity {
+    text(parent, TextRole::Title, value)
+}
+
+pub fn label(parent: &mut ChildBuilder, value: impl Into<String>) -> Entity {
+    text(parent, TextRole::Body, value)
+}
+
+/// A boxed off column of widgets
+pub fn panel(
+    parent: &mut ChildBuilder,
+    theme: &Theme,
+    children: impl FnOnce(&mut ChildBuilder),
+) -> Entity {
+    parent
+        .spawn((
+            Surface::Panel,
+            NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    padding: UiRect::all(Val::Px(15.)),
+                    gap: Size::height(Val::Px(5.)),
+                    ..default()
+                },
+                background_color: theme.panel.into(),
+                ..default()
+            },
+        ))
+        .with_children(children)
+        .id()
+}
+
+/// A fixed width panel, for laying out in a `grid`
+pub fn card(
+    parent: &mut ChildBuilder,
+    theme: &Theme,
+    children: impl FnOnce(&mut ChildBuilder),
+) -> Entity {
+    parent
+        .spawn((
+            Surface::Panel,
+            NodeBundle {
+                style: Style {
+                    size: Size::width(Val::Px(420.)),
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(10.)),
+                    ..default()
+                },
+                background_color: theme.panel.into(),
+                ..default()
+            },
+        ))
+        .with_children(children)
+        .id()
+}
+
+/// Lays widgets out in rows, wrapping onto a new row when one fills up
+pub fn grid(parent: &mut ChildBuilder, children: impl FnOnce(&mut ChildBuilder)) -> Entity {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                max_size: Size::width(Val::Px(880.)),
+                flex_wrap: FlexWrap::Wrap,
+                justify_content: JustifyContent::Center,
+                gap: Size::all(Val::Px(10.)),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(children)
+        .id()
+}
+
+/// Lays widgets out side by side
+pub fn row(parent: &mut ChildBuilder, children: impl FnOnce(&mut ChildBuilder)) -> Entity {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                margin: UiRect::top(Val::Px(20.)),
+                gap: Size::width(Val::Px(20.)),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(children)
+        .id()
+}
+
+/// A button with a text label, its colors follow the theme as it's hovered, pressed and focused
+pub fn button(
+    parent: &mut ChildBuilder,
+    theme: &Theme,
+    label: &str,
+    marker: impl Bundle,
+) -> Entity {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    min_size: Size::width(Val::Px(220.)),
+                    padding: UiRect::all(Val::Px(10.)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: theme.button.into(),
+                ..default()
+            },
+            marker,
+        ))
+        .with_children(|parent| {
+            text(parent, TextRole::Heading, label);
+        })
+        .id()
+}
+
+/// A button with one of the icons from the menu art next to its label
+pub fn icon_button(
+    parent: &mut ChildBuilder,
+    theme: &Theme,
+    icon: Handle<Image>,
+    label: &str,
+    marker: impl Bundle,
+) -> Entity {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    size: Size::new(Val::Px(320.), Val::Px(60.)),
+                    padding: UiRect::horizontal(Val::Px(10.)),
+                    align_items: AlignItems::Center,
+                    gap: Size::width(Val::Px(15.)),
+                    ..default()
+                },
+                background_color: theme.button.into(),
+                ..default()
+            },
+            marker,
+        ))
+        .with_children(|parent| {
+            parent.spawn(ImageBundle {
+                style: Style {
+                    size: Size::new(Val::Px(42.), Val::Px(44.)),
+                    ..default()
+                },
+                image: UiImage::new(icon),
+                ..default()
+            });
+
+            text(parent, TextRole::Heading, label);
+        })
+        .id()
+}
+
+/// Replaces the label of a button spawned by `button` or `icon_button`
+pub fn set_button_label(children: &Children, text_q: &mut Query<&mut UiText>, label: &str) {
+    for &child in children.iter() {
+        if let Ok(mut text) = text_q.get_mut(child) {
+            text.value = label.to_string();
+        }
+    }
+}
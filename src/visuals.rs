@@ -0,0 +1,54 @@
+use bevy::{prelude::*, sprite::Mesh2dHandle};
+
+use crate::enemy::FishType;
+
+/// Meshes and materials made once at startup and shared by every fish, bomb and explosion,
+/// so spawning them never adds new assets.
+pub struct VisualsPlugin;
+
+impl Plugin for VisualsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Visuals>();
+    }
+}
+
+#[derive(Resource)]
+pub struct Visuals {
+    pub fish_mesh: Mesh2dHandle,
+    basic_fish: Handle<ColorMaterial>,
+    turtle: Handle<ColorMaterial>,
+    pub bomb_mesh: Mesh2dHandle,
+    pub bomb_material: Handle<ColorMaterial>,
+    pub explosion_mesh: Mesh2dHandle,
+    pub explosion_material: Handle<ColorMaterial>,
+}
+
+impl Visuals {
+    pub fn fish_material(&self, fish_type: FishType) -> Handle<ColorMaterial> {
+        match fish_type {
+            FishType::Basic => self.basic_fish.clone(),
+            FishType::Turtle => self.turtle.clone(),
+        }
+    }
+}
+
+impl FromWorld for Visuals {
+    fn from_world(world: &mut World) -> Self {
+        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+        let fish_mesh = meshes.add(shape::Quad::new(Vec2::new(15., 10.)).into());
+        let bomb_mesh = meshes.add(shape::Circle::new(12.).into());
+        let explosion_mesh = meshes.add(shape::Circle::new(25.).into());
+
+        let mut materials = world.resource_mut::<Assets<ColorMaterial>>();
+
+        Visuals {
+            fish_mesh: fish_mesh.into(),
+            basic_fish: materials.add(ColorMaterial::from(Color::hex("fc6a03").unwrap())),
+            turtle: materials.add(ColorMaterial::from(Color::hex("3cb043").unwrap())),
+            bomb_mesh: bomb_mesh.into(),
+            bomb_material: materials.add(ColorMaterial::from(Color::BLACK)),
+            explosion_mesh: explosion_mesh.into(),
+            explosion_material: materials.add(ColorMaterial::from(Color::ORANGE_RED)),
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use bevy::prelude::*;
+
+use gonefishin::{input::GameInput, player::Character, replay::Recording};
+
+const SEED: u64 = 0x0123_4567_89AB_CDEF;
+
+// a replay file laid out by hand: magic, version, seed, level, character, tick count, ticks
+fn replay_file(magic: &[u8], version: u8, character: u8, count: u32, ticks: &[u8]) -> Vec<u8> {
+    let mut bytes = magic.to_vec();
+    bytes.push(version);
+    bytes.extend_from_slice(&SEED.to_le_bytes());
+    bytes.push(1);
+    bytes.push(character);
+    bytes.extend_from_slice(&count.to_le_bytes());
+    bytes.extend_from_slice(ticks);
+    bytes
+}
+
+fn ticks() -> Vec<u8> {
+    [
+        GameInput::default(),
+        GameInput {
+            left: true,
+            ..default()
+        },
+        GameInput {
+            right: true,
+            drop: true,
+            ..default()
+        },
+        GameInput {
+            collect: true,
+            ..default()
+        },
+    ]
+    .map(GameInput::to_bits)
+    .to_vec()
+}
+
+#[test]
+fn a_replay_reads_back_the_same() {
+    let bytes = replay_file(b"GFRP", 2, 2, 4, &ticks());
+
+    let recording = Recording::parse(&bytes).unwrap();
+
+    assert_eq!(recording.seed, SEED);
+    assert_eq!(recording.level, 1);
+    assert_eq!(recording.character, Character::PinkMan);
+    assert_eq!(recording.serialize(), bytes);
+}
+
+#[test]
+fn an_empty_replay_reads_back_the_same() {
+    let bytes = replay_file(b"GFRP", 2, 0, 0, &[]);
+
+    assert_eq!(Recording::parse(&bytes).unwrap().serialize(), bytes);
+}
+
+#[test]
+fn a_file_that_isnt_a_replay_is_refused() {
+    let bytes = replay_file(b"GFRX", 2, 2, 4, &ticks());
+
+    assert!(Recording::parse(&bytes).is_err());
+}
+
+#[test]
+fn a_replay_from_another_version_is_refused() {
+    for version in [1, 3] {
+        let bytes = replay_file(b"GFRP", version, 2, 4, &ticks());
+
+        assert!(
+            Recording::parse(&bytes).is_err(),
+            "read version {}",
+            version
+        );
+    }
+}
+
+#[test]
+fn a_replay_with_the_wrong_tick_count_is_refused() {
+    for count in [3, 5] {
+        let bytes = replay_file(b"GFRP", 2, 2, count, &ticks());
+
+        assert!(Recording::parse(&bytes).is_err(), "read {} ticks", count);
+    }
+}
+
+#[test]
+fn a_replay_with_an_unknown_character_is_refused() {
+    for character in [Character::ALL.len() as u8, u8::MAX] {
+        let bytes = replay_file(b"GFRP", 2, character, 4, &ticks());
+
+        assert!(
+            Recording::parse(&bytes).is_err(),
+            "read character {}",
+            character
+        );
+    }
+}
+
+#[test]
+fn a_replay_cut_off_in_its_header_is_refused() {
+    let bytes = replay_file(b"GFRP", 2, 2, 4, &ticks());
+
+    for len in 0..19 {
+        assert!(
+            Recording::parse(&bytes[..len]).is_err(),
+            "read {} bytes",
+            len
+        );
+    }
+}
@@ -0,0 +1,293 @@
+use bevy::prelude::*;
+
+use std::time::Duration;
+
+use gonefishin::{
+    enemy::{Fish, FishSpawnConfig, FishType},
+    events::EventsPlugin,
+    game_over::GameOverPlugin,
+    headless::Simulation,
+    highscore::{HighScorePlugin, HighScores, NameEntry},
+    input::{GameInput, InputSource},
+    level::SelectedLevel,
+    menu::BackTarget,
+    player::{Bomb, Character},
+    playfield::Playfield,
+    replay::ReplayRequest,
+    rng::GameRng,
+    savegame::RunSnapshot,
+    score::Score,
+    state::AppState,
+    stats::{BestScore, RunStats},
+    transition::{ScreenTransition, TransitionStyle, Transitions},
+    types::Dir,
+    ui::{
+        focus::FocusPlugin,
+        theme::{FontFace, Theme, ThemeKind},
+    },
+};
+
+const DROP: GameInput = GameInput {
+    left: false,
+    right: false,
+    drop: true,
+    collect: false,
+};
+
+const COLLECT: GameInput = GameInput {
+    left: false,
+    right: false,
+    drop: false,
+    collect: true,
+};
+
+fn running() -> Simulation {
+    let mut sim = Simulation::new(Playfield::default());
+    sim.start_run();
+    sim
+}
+
+// drops a bomb from where the player stands and sets it off straight away
+fn bomb_the_player_spot(sim: &mut Simulation) {
+    sim.step(DROP);
+    sim.step(DROP);
+    sim.idle_for(5);
+}
+
+#[test]
+fn run_starts_with_no_score() {
+    let sim = running();
+
+    assert_eq!(sim.state(), AppState::Running);
+    assert_eq!(sim.score(), 0);
+}
+
+#[test]
+fn collecting_a_basic_fish_adds_100() {
+    let mut sim = running();
+    let below_player = sim.player_position() - Vec2::new(0., 20.);
+    let fish = sim.spawn_fish(FishType::Basic, Dir::Up, below_player);
+
+    // let the physics pick up the new fish before reaching for it
+    sim.idle_for(2);
+    sim.step(COLLECT);
+    sim.idle_for(2);
+
+    assert_eq!(sim.score(), 100);
+    // caught fish go back to the pool rather than being despawned
+    assert!(sim.app.world.get::<Fish>(fish).is_none());
+}
+
+#[test]
+fn collecting_needs_the_button() {
+    let mut sim = running();
+    let below_player = sim.player_position() - Vec2::new(0., 20.);
+    sim.spawn_fish(FishType::Basic, Dir::Up, below_player);
+
+    sim.idle_for(5);
+
+    assert_eq!(sim.score(), 0);
+}
+
+#[test]
+fn detonating_next_to_a_basic_fish_kills_it() {
+    let mut sim = running();
+    let near_player = sim.player_position() - Vec2::new(0., 10.);
+    let fish = sim.spawn_fish(FishType::Basic, Dir::Forward, near_player);
+
+    bomb_the_player_spot(&mut sim);
+
+    assert_eq!(sim.state(), AppState::Running);
+    assert!(!sim.app.world.get::<Fish>(fish).unwrap().is_alive());
+}
+
+#[test]
+fn detonating_next_to_a_turtle_ends_the_run() {
+    let mut sim = running();
+    let near_player = sim.player_position() - Vec2::new(0., 10.);
+    sim.spawn_fish(FishType::Turtle, Dir::Forward, near_player);
+
+    bomb_the_player_spot(&mut sim);
+
+    assert_eq!(sim.state(), AppState::GameOver);
+}
+
+#[test]
+fn losing_a_run_cuts_straight_to_game_over() {
+    let style = Transitions::default().style(&AppState::Running, &AppState::GameOver);
+
+    assert_eq!(style, TransitionStyle::Cut);
+}
+
+#[test]
+fn moving_follows_the_input() {
+    let mut sim = running();
+    let start = sim.player_position();
+
+    for _ in 0..10 {
+        sim.step(GameInput {
+            right: true,
+            ..default()
+        });
+    }
+
+    assert!(sim.player_position().x > start.x);
+}
+
+#[test]
+fn restarting_after_a_game_over_starts_clean() {
+    let mut sim = running();
+    let near_player = sim.player_position() - Vec2::new(0., 10.);
+    sim.spawn_fish(FishType::Basic, Dir::Forward, near_player);
+
+    // leave a bomb sinking and the spawn timer most of the way to the next fish
+    sim.step(DROP);
+    sim.idle_for(90);
+
+    sim.app
+        .world
+        .resource_mut::<NextState<AppState>>()
+        .set(AppState::GameOver);
+    sim.idle();
+    assert_eq!(sim.state(), AppState::GameOver);
+
+    sim.start_run();
+
+    let world = &mut sim.app.world;
+    assert_eq!(world.query::<&Bomb>().iter(world).count(), 0);
+    assert_eq!(world.query::<&Fish>().iter(world).count(), 0);
+    assert!(world.resource::<FishSpawnConfig>().timer.elapsed() < Duration::from_secs(1));
+}
+
+// a run saved partway through its second wave, as it would be written to disk
+const SAVED_RUN: &str = "gonefishin-run 2
+rng\t42\t1234567
+level\t0
+character\t2
+score\t1200
+combo\t0\t0
+spawner\t500000000\t2\t9\t1
+stats\t3\t6\t4\t30000000000
+player\t-120.5\t242\tBackward
+species\tBasic\t4\t8
+fish\tBasic\tForward\t2\t40\t-100\t0\t0
+fish\tTurtle\tBackward\t2\t-300\t-150\t0\t0
+end
+";
+
+#[test]
+fn continuing_a_saved_run_puts_it_back() {
+    let mut sim = Simulation::new(Playfield::default());
+    sim.resume_run(RunSnapshot::parse(SAVED_RUN).unwrap());
+
+    assert_eq!(sim.state(), AppState::Running);
+    assert_eq!(sim.score(), 1200);
+    assert_eq!(sim.player_position(), Vec2::new(-120.5, 242.));
+
+    let world = &mut sim.app.world;
+    assert_eq!(*world.resource::<Character>(), Character::PinkMan);
+    assert_eq!(world.resource::<GameRng>().seed(), 42);
+
+    let config = world.resource::<FishSpawnConfig>();
+    assert_eq!((config.wave, config.spawned, config.cleared), (2, 9, 1));
+
+    let stats = world.resource::<RunStats>();
+    assert_eq!(
+        (stats.escaped, stats.bombs_used, stats.bombs_hit),
+        (3, 6, 4)
+    );
+    assert_eq!(stats.species[&FishType::Basic].collected, 8);
+
+    let mut fish_types: Vec<_> = world
+        .query::<&Fish>()
+        .iter(world)
+        .map(|f| f.fish_type)
+        .collect();
+    fish_types.sort_by_key(|t| *t == FishType::Turtle);
+    assert_eq!(fish_types, [FishType::Basic, FishType::Turtle]);
+}
+
+// the game over screen on its own, after a run that scored `score`
+fn game_over_screen(score: u32) -> App {
+    let mut app = App::new();
+
+    app.add_plugins(MinimalPlugins)
+        .add_state::<AppState>()
+        .init_resource::<Input<KeyCode>>()
+        .init_resource::<Input<GamepadButton>>()
+        .init_resource::<Gamepads>()
+        .add_event::<ReceivedCharacter>()
+        .add_event::<ReplayRequest>()
+        .insert_resource(Theme::new(
+            ThemeKind::Light,
+            FontFace::Smooth,
+            Handle::default(),
+        ))
+        .insert_resource(BackTarget(AppState::MainMenu))
+        .insert_resource(InputSource::Keyboard)
+        .insert_resource(Score { val: score })
+        .insert_resource(GameRng::new(1))
+        .init_resource::<SelectedLevel>()
+        .init_resource::<Character>()
+        .init_resource::<RunStats>()
+        .init_resource::<BestScore>()
+        .init_resource::<ScreenTransition>()
+        .add_plugin(EventsPlugin)
+        .add_plugin(FocusPlugin)
+        .add_plugin(HighScorePlugin)
+        .add_plugin(GameOverPlugin);
+
+    app.update();
+    // an empty table that's never written, rather than the one on this machine
+    app.insert_resource(HighScores::default());
+
+    app.world
+        .resource_mut::<NextState<AppState>>()
+        .set(AppState::GameOver);
+    app.update();
+
+    app
+}
+
+#[test]
+fn enter_saves_the_name_and_stays_on_game_over() {
+    let mut app = game_over_screen(500);
+    assert!(app.world.resource::<NameEntry>().active);
+
+    app.world
+        .resource_mut::<Input<KeyCode>>()
+        .press(KeyCode::Return);
+    app.update();
+
+    let entry = app.world.resource::<NameEntry>();
+    assert!(!entry.active);
+    assert_eq!(entry.saved_rank, Some(0));
+    // the press was used up by the name, so no button can take it too
+    assert!(!app
+        .world
+        .resource::<Input<KeyCode>>()
+        .just_pressed(KeyCode::Return));
+
+    app.world.resource_mut::<Input<KeyCode>>().clear();
+    app.update();
+
+    assert_eq!(
+        app.world.resource::<State<AppState>>().0,
+        AppState::GameOver
+    );
+}
+
+#[test]
+fn leaving_game_over_without_a_name_still_saves_the_score() {
+    let mut app = game_over_screen(500);
+
+    app.world
+        .resource_mut::<NextState<AppState>>()
+        .set(AppState::MainMenu);
+    app.update();
+
+    let table = app.world.resource::<HighScores>();
+    assert_eq!(table.entries.len(), 1);
+    assert_eq!(table.entries[0].name, "Player");
+    assert_eq!(table.entries[0].score, 500);
+}
@@ -0,0 +1,130 @@
+use bevy::prelude::*;
+
+use std::time::Duration;
+
+use gonefishin::{
+    enemy::FishType,
+    player::Character,
+    savegame::{RunSnapshot, SavedFish},
+    stats::SpeciesStats,
+    types::Dir,
+};
+
+// a run with something in every field, and positions that don't round to tidy decimals
+fn snapshot() -> RunSnapshot {
+    RunSnapshot {
+        seed: 0xDEAD_BEEF_1234,
+        rng_state: u64::MAX - 7,
+        level: 1,
+        character: Character::PinkMan,
+        score: 4200,
+        combo: 3,
+        combo_elapsed: Duration::from_millis(1250),
+        spawn_elapsed: Duration::from_nanos(333_333_333),
+        wave: 4,
+        spawned: 17,
+        cleared: 3,
+        escaped: 5,
+        bombs_used: 12,
+        bombs_hit: 9,
+        duration: Duration::from_secs(95),
+        species: vec![
+            (
+                FishType::Basic,
+                SpeciesStats {
+                    killed: 20,
+                    collected: 18,
+                },
+            ),
+            (
+                FishType::Turtle,
+                SpeciesStats {
+                    killed: 0,
+                    collected: 2,
+                },
+            ),
+        ],
+        player_position: Vec2::new(-123.456, 242.1),
+        player_facing: Dir::Backward,
+        fish: vec![
+            SavedFish {
+                fish_type: FishType::Basic,
+                direction: Dir::Forward,
+                wave: 4,
+                position: Vec2::new(1. / 3., -200.7),
+                drift: Vec2::new(0.1, -0.025),
+            },
+            SavedFish {
+                fish_type: FishType::Turtle,
+                direction: Dir::Up,
+                wave: 3,
+                position: Vec2::new(f32::MIN_POSITIVE, 1e-7),
+                drift: Vec2::ZERO,
+            },
+        ],
+        bomb: Some((Vec2::new(10.5, -3.3), Vec2::new(-0.2, 0.))),
+    }
+}
+
+// the saved run with its header swapped for `header`
+fn with_header(header: &str) -> String {
+    let saved = snapshot().serialize();
+    let (_, rest) = saved.split_once('\n').unwrap();
+
+    format!("{}\n{}", header, rest)
+}
+
+#[test]
+fn a_saved_run_reads_back_the_same() {
+    let saved = snapshot();
+
+    assert_eq!(RunSnapshot::parse(&saved.serialize()), Ok(saved));
+}
+
+#[test]
+fn a_saved_run_without_a_bomb_reads_back_the_same() {
+    let saved = RunSnapshot {
+        bomb: None,
+        fish: vec![],
+        ..snapshot()
+    };
+
+    assert_eq!(RunSnapshot::parse(&saved.serialize()), Ok(saved));
+}
+
+#[test]
+fn a_run_from_a_newer_version_is_refused() {
+    let err = RunSnapshot::parse(&with_header("gonefishin-run 99")).unwrap_err();
+
+    assert!(err.contains("newer version"), "{}", err);
+}
+
+#[test]
+fn a_run_from_an_old_or_unknown_version_is_refused() {
+    for header in ["gonefishin-run 1", "gonefishin-run 0", "gonefishin-run two"] {
+        assert!(
+            RunSnapshot::parse(&with_header(header)).is_err(),
+            "{}",
+            header
+        );
+    }
+}
+
+#[test]
+fn a_run_without_its_header_is_refused() {
+    assert!(RunSnapshot::parse(&with_header("some other file")).is_err());
+}
+
+#[test]
+fn a_cut_off_run_is_refused() {
+    let saved = snapshot().serialize();
+
+    // everything short of the last newline, which isn't needed
+    for len in 0..saved.len() - 1 {
+        assert!(
+            RunSnapshot::parse(&saved[..len]).is_err(),
+            "read a save cut off after {} bytes",
+            len
+        );
+    }
+}